@@ -0,0 +1,451 @@
+use std::collections::HashMap;
+use std::time::Duration;
+
+use reqwest::header::{HeaderMap, HeaderValue, ACCEPT, AUTHORIZATION};
+use reqwest::{Client, RequestBuilder, Response, StatusCode};
+use serde::de::DeserializeOwned;
+
+use crate::cluster::ClusterInfo;
+use crate::config::ClusterContext;
+use crate::connect::{
+    Connector, ConnectorConfig, ConnectorStatus, CreateConnector, DescribeConnector,
+    VerboseConnector,
+};
+use crate::connector_plugins::{ConfigKey, ConfigValidation, ConnectorPlugin};
+use crate::error::{KofrError, Result};
+use crate::loggers::{LoggerLevel, Loggers, Scope};
+use crate::offsets::{ConnectorOffsets, OffsetsResponse};
+use crate::tasks::{TaskResponse, TaskStatus};
+use crate::topics::Topic;
+
+/// A non-blocking client of a Connect cluster's REST API, returning the same models as
+/// [`crate::HTTPClient`]. Requests are sent to a single host and are neither retried nor failed
+/// over to the other hosts of the cluster.
+///
+/// ```no_run
+/// use kofr::config::ClusterContext;
+/// use kofr::async_client::AsyncHTTPClient;
+///
+/// # async fn run() -> kofr::error::Result<()> {
+/// let cluster = ClusterContext {
+///     name: "dev".to_string(),
+///     hosts: vec!["http://localhost:8083".to_string()],
+///     ..Default::default()
+/// };
+/// let client = AsyncHTTPClient::for_cluster(&cluster).await?;
+/// for connector in client.list_connectors_status().await? {
+///     println!("{}: {}", connector.name, connector.state);
+/// }
+/// # Ok(())
+/// # }
+/// ```
+pub struct AsyncHTTPClient {
+    client: Client,
+    connect_uri: String,
+}
+
+impl AsyncHTTPClient {
+    /// builds a client sending requests to the given host
+    pub fn new(client: Client, connect_uri: &str) -> Self {
+        Self {
+            client,
+            connect_uri: connect_uri.trim_end_matches('/').to_string(),
+        }
+    }
+
+    /// builds a client using the cluster's TLS settings and credentials, talking to the first
+    /// host of the cluster that answers
+    pub async fn for_cluster(cluster: &ClusterContext) -> Result<Self> {
+        let client = cluster_client(cluster)?;
+        let mut last_error = None;
+        for host in &cluster.hosts {
+            match client.get(host).send().await {
+                Ok(response) if response.status().is_success() => {
+                    return Ok(Self::new(client, host))
+                }
+                Ok(response)
+                    if matches!(
+                        response.status(),
+                        StatusCode::UNAUTHORIZED | StatusCode::FORBIDDEN
+                    ) =>
+                {
+                    return Err(KofrError::Auth(format!(
+                        "host \"{}\" of cluster: \"{}\" rejected the credentials ({})",
+                        host,
+                        cluster.name,
+                        response.status().as_u16()
+                    )))
+                }
+                Ok(response) => last_error = Some(format!("status code {}", response.status())),
+                Err(err) => last_error = Some(err.to_string()),
+            }
+        }
+        let error = format!(
+            "client has run out of available hosts to talk to for cluster: \"{}\"",
+            cluster.name
+        );
+        match last_error {
+            Some(last_error) => Err(KofrError::Transport(format!(
+                "{}, last error: {}",
+                error, last_error
+            ))),
+            None => Err(KofrError::Transport(error)),
+        }
+    }
+
+    /// lists connectors with their status and class wrapping GET request to
+    /// /connectors?expand=status&expand=info
+    pub async fn list_connectors_status(&self) -> Result<Vec<VerboseConnector>> {
+        let request = self
+            .get("/connectors")
+            .query(&[("expand", "status"), ("expand", "info")]);
+        let response = send(request, None).await?;
+        crate::connect::verbose_connectors(&response.text().await?)
+    }
+
+    /// lists the names of active connectors wrapping GET request to /connectors
+    pub async fn list_connectors(&self) -> Result<Vec<String>> {
+        json(send(self.get("/connectors"), None).await?).await
+    }
+
+    /// creates a connector wrapping POST request to /connectors
+    pub async fn create_connector(&self, c: &CreateConnector) -> Result<Connector> {
+        let request = self.request(reqwest::Method::POST, "/connectors").json(c);
+        json(send(request, None).await?).await
+    }
+
+    /// config, tasks and type of a connector wrapping GET request to /connectors/<name>
+    pub async fn get_connector(&self, name: &str) -> Result<Connector> {
+        let request = self.get(&format!("/connectors/{}", name));
+        json(send(request, Some(("connector", name))).await?).await
+    }
+
+    /// config and status of a connector
+    pub async fn describe_connector(&self, name: &str) -> Result<DescribeConnector> {
+        let status = self.get_connector_status(name).await?;
+        let config = self.get_connector_config(name).await?;
+
+        Ok(DescribeConnector {
+            name: status.name,
+            connector_type: status.connector_type,
+            config,
+            state: status.connector_state,
+            tasks: status.tasks,
+        })
+    }
+
+    /// updates a connector's config wrapping PUT request to /connectors/<name>/config
+    pub async fn put_connector(&self, name: &str, config: ConnectorConfig) -> Result<Connector> {
+        let request = self
+            .request(
+                reqwest::Method::PUT,
+                &format!("/connectors/{}/config", name),
+            )
+            .json(&config);
+        json(send(request, Some(("connector", name))).await?).await
+    }
+
+    /// wraps GET request to /connectors/<name>/config
+    pub async fn get_connector_config(&self, name: &str) -> Result<ConnectorConfig> {
+        let request = self.get(&format!("/connectors/{}/config", name));
+        json(send(request, Some(("connector", name))).await?).await
+    }
+
+    /// wraps GET request to /connectors/<name>/status
+    pub async fn get_connector_status(&self, name: &str) -> Result<ConnectorStatus> {
+        let request = self.get(&format!("/connectors/{}/status", name));
+        json(send(request, Some(("connector", name))).await?).await
+    }
+
+    /// restarts a connector wrapping POST request to /connectors/<name>/restart, returns the
+    /// status of the restarting connector and tasks when tasks are included
+    pub async fn restart_connector(
+        &self,
+        name: &str,
+        include_tasks: bool,
+        only_failed: bool,
+    ) -> Result<Option<ConnectorStatus>> {
+        let request = self
+            .request(
+                reqwest::Method::POST,
+                &format!("/connectors/{}/restart", name),
+            )
+            .query(&[("includeTasks", include_tasks), ("onlyFailed", only_failed)]);
+        let response = send(request, Some(("connector", name))).await?;
+        match response.status() {
+            StatusCode::ACCEPTED => Ok(Some(json(response).await?)),
+            _ => Ok(None),
+        }
+    }
+
+    /// pauses a connector and its tasks wrapping PUT request to /connectors/<name>/pause
+    pub async fn pause_connector(&self, name: &str) -> Result<()> {
+        self.put_state(name, "pause").await
+    }
+
+    /// stops the connector and shuts down its tasks wrapping PUT request to /connectors/<name>/stop
+    pub async fn stop_connector(&self, name: &str) -> Result<()> {
+        self.put_state(name, "stop").await
+    }
+
+    /// resumes a paused or stopped connector wrapping PUT request to /connectors/<name>/resume
+    pub async fn resume_connector(&self, name: &str) -> Result<()> {
+        self.put_state(name, "resume").await
+    }
+
+    /// deletes a connector and its config wrapping DELETE request to /connectors/<name>
+    pub async fn delete_connector(&self, name: &str) -> Result<()> {
+        let request = self.request(reqwest::Method::DELETE, &format!("/connectors/{}", name));
+        send(request, Some(("connector", name))).await?;
+        Ok(())
+    }
+
+    /// wraps GET request to /connectors/<name>/offsets
+    pub async fn get_connector_offsets(&self, name: &str) -> Result<ConnectorOffsets> {
+        let request = self.get(&format!("/connectors/{}/offsets", name));
+        json(send(request, Some(("connector", name))).await?).await
+    }
+
+    /// alters offsets of a stopped connector wrapping PATCH request to /connectors/<name>/offsets
+    pub async fn alter_connector_offsets(
+        &self,
+        name: &str,
+        offsets: &ConnectorOffsets,
+    ) -> Result<OffsetsResponse> {
+        let request = self
+            .request(
+                reqwest::Method::PATCH,
+                &format!("/connectors/{}/offsets", name),
+            )
+            .json(offsets);
+        json(send(request, Some(("connector", name))).await?).await
+    }
+
+    /// resets all offsets of a stopped connector wrapping DELETE request to /connectors/<name>/offsets
+    pub async fn reset_connector_offsets(&self, name: &str) -> Result<OffsetsResponse> {
+        let request = self.request(
+            reqwest::Method::DELETE,
+            &format!("/connectors/{}/offsets", name),
+        );
+        json(send(request, Some(("connector", name))).await?).await
+    }
+
+    /// lists the tasks of a connector with their config wrapping GET request to
+    /// /connectors/<name>/tasks
+    pub async fn list_tasks(&self, connector_name: &str) -> Result<Vec<TaskResponse>> {
+        let request = self.get(&format!("/connectors/{}/tasks", connector_name));
+        json(send(request, Some(("connector", connector_name))).await?).await
+    }
+
+    /// wraps GET request to /connectors/<name>/tasks/<id>/status
+    pub async fn task_status(&self, connector_name: &str, task_id: usize) -> Result<TaskStatus> {
+        let task = format!("{}/{}", connector_name, task_id);
+        let request = self.get(&format!(
+            "/connectors/{}/tasks/{}/status",
+            connector_name, task_id
+        ));
+        json(send(request, Some(("task", &task))).await?).await
+    }
+
+    /// wraps POST request to /connectors/<name>/tasks/<id>/restart
+    pub async fn restart_task(&self, connector_name: &str, task_id: usize) -> Result<()> {
+        let task = format!("{}/{}", connector_name, task_id);
+        let request = self.request(
+            reqwest::Method::POST,
+            &format!("/connectors/{}/tasks/{}/restart", connector_name, task_id),
+        );
+        send(request, Some(("task", &task))).await?;
+        Ok(())
+    }
+
+    /// topics used by a connector since it was created or its topics were reset, wrapping GET
+    /// request to /connectors/<name>/topics
+    pub async fn list_topics(&self, connector_name: &str) -> Result<Topic> {
+        let request = self.get(&format!("/connectors/{}/topics", connector_name));
+        json(send(request, Some(("connector", connector_name))).await?).await
+    }
+
+    /// wraps PUT request to /connectors/<name>/topics/reset
+    pub async fn reset_topics(&self, connector_name: &str) -> Result<()> {
+        let request = self.request(
+            reqwest::Method::PUT,
+            &format!("/connectors/{}/topics/reset", connector_name),
+        );
+        send(request, Some(("connector", connector_name))).await?;
+        Ok(())
+    }
+
+    /// lists the connector plugins installed on the worker wrapping GET request to
+    /// /connector-plugins
+    pub async fn list_plugins(&self) -> Result<Vec<ConnectorPlugin>> {
+        json(send(self.get("/connector-plugins"), None).await?).await
+    }
+
+    /// validates a connector config against the config definition of a plugin, wrapping PUT
+    /// request to /connector-plugins/<class>/config/validate
+    pub async fn validate_config(
+        &self,
+        name: &str,
+        config: HashMap<String, String>,
+    ) -> Result<ConfigValidation> {
+        let request = self
+            .request(
+                reqwest::Method::PUT,
+                &format!("/connector-plugins/{}/config/validate", name),
+            )
+            .json(&config);
+        json(send(request, None).await?).await
+    }
+
+    /// definitions of the config keys of a plugin wrapping GET request to
+    /// /connector-plugins/<class>/config
+    pub async fn plugin_config(&self, class: &str) -> Result<Vec<ConfigKey>> {
+        let request = self.get(&format!("/connector-plugins/{}/config", class));
+        json(send(request, Some(("plugin", class))).await?).await
+    }
+
+    /// wraps GET request to /admin/loggers
+    pub async fn list_loggers(&self) -> Result<Loggers> {
+        json(send(self.get("/admin/loggers"), None).await?).await
+    }
+
+    /// wraps GET request to /admin/loggers/<name>
+    pub async fn get_logger(&self, name: &str) -> Result<LoggerLevel> {
+        let request = self.get(&format!("/admin/loggers/{}", name));
+        json(send(request, Some(("logger", name))).await?).await
+    }
+
+    /// sets a logger's level wrapping PUT request to /admin/loggers/<name>,
+    /// returns the affected loggers for worker scope, cluster scoped changes are applied asynchronously
+    pub async fn set_logger_level(
+        &self,
+        name: &str,
+        level: &str,
+        scope: Scope,
+    ) -> Result<Vec<String>> {
+        let request = self
+            .request(reqwest::Method::PUT, &format!("/admin/loggers/{}", name))
+            .query(&[("scope", scope.to_string())])
+            .json(&serde_json::json!({ "level": level }));
+        let response = send(request, None).await?;
+        match response.status() {
+            StatusCode::NO_CONTENT => Ok(Vec::new()),
+            _ => json(response).await,
+        }
+    }
+
+    /// version of the worker and id of its kafka cluster wrapping GET request to /
+    pub async fn cluster_info(&self) -> Result<ClusterInfo> {
+        json(send(self.get("/"), None).await?).await
+    }
+
+    async fn put_state(&self, name: &str, action: &str) -> Result<()> {
+        let request = self.request(
+            reqwest::Method::PUT,
+            &format!("/connectors/{}/{}", name, action),
+        );
+        send(request, Some(("connector", name))).await?;
+        Ok(())
+    }
+
+    fn get(&self, path: &str) -> RequestBuilder {
+        self.request(reqwest::Method::GET, path)
+    }
+
+    fn request(&self, method: reqwest::Method, path: &str) -> RequestBuilder {
+        self.client
+            .request(method, format!("{}{}", self.connect_uri, path))
+            .header(ACCEPT, "application/json")
+    }
+}
+
+/// client using the cluster's TLS settings and sending its credentials, if any, with every request
+fn cluster_client(cluster: &ClusterContext) -> Result<Client> {
+    let config_error = |err: anyhow::Error| KofrError::Config(format!("{err:#}"));
+    let mut builder = Client::builder().timeout(Duration::from_secs(5));
+    if let Some(tls_config) = crate::tls::client_config(cluster).map_err(config_error)? {
+        builder = builder.use_preconfigured_tls((*tls_config).clone());
+    }
+    if let Some(auth) = &cluster.auth {
+        let mut value = HeaderValue::from_str(&auth.header()?)
+            .map_err(|err| KofrError::Config(format!("invalid credentials: {err}")))?;
+        value.set_sensitive(true);
+        builder = builder.default_headers(HeaderMap::from_iter([(AUTHORIZATION, value)]));
+    }
+    builder
+        .build()
+        .map_err(|err| KofrError::Config(err.to_string()))
+}
+
+/// sends a request, turning error responses into errors, 404s into `NotFound` errors of the
+/// given resource when one is given
+async fn send(request: RequestBuilder, resource: Option<(&'static str, &str)>) -> Result<Response> {
+    let response = request.send().await?;
+    let status = response.status();
+    if status.is_success() {
+        return Ok(response);
+    }
+    if let (StatusCode::NOT_FOUND, Some((resource, name))) = (status, resource) {
+        return Err(KofrError::not_found(resource, name));
+    }
+    let body = response.text().await?;
+    Err(KofrError::from_response(status.as_u16(), &body))
+}
+
+async fn json<T: DeserializeOwned>(response: Response) -> Result<T> {
+    Ok(serde_json::from_slice(&response.bytes().await?)?)
+}
+
+impl From<reqwest::Error> for KofrError {
+    fn from(err: reqwest::Error) -> Self {
+        KofrError::Transport(err.to_string())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use kcmockserver::KcTestServer;
+
+    #[test]
+    fn test_list_connectors_async() {
+        let server = KcTestServer::new();
+        let client = AsyncHTTPClient::new(Client::new(), &server.base_url().to_string());
+        let runtime = tokio::runtime::Builder::new_current_thread()
+            .enable_all()
+            .build()
+            .unwrap();
+
+        let connectors = runtime.block_on(client.list_connectors_status()).unwrap();
+        assert!(connectors.is_empty());
+    }
+
+    #[test]
+    fn test_error_responses_async() {
+        let mut server = mockito::Server::new();
+        let missing = server
+            .mock("GET", "/connectors/missing")
+            .with_status(404)
+            .with_body(r#"{"error_code":404,"message":"Connector missing not found"}"#)
+            .create();
+        let rejected = server
+            .mock("GET", "/connectors")
+            .with_status(401)
+            .with_body(r#"{"error_code":401,"message":"Unauthorized"}"#)
+            .create();
+        let client = AsyncHTTPClient::new(Client::new(), &server.url());
+        let runtime = tokio::runtime::Builder::new_current_thread()
+            .enable_all()
+            .build()
+            .unwrap();
+
+        let err = runtime
+            .block_on(client.get_connector("missing"))
+            .unwrap_err();
+        assert!(matches!(err, KofrError::NotFound { .. }), "{err:?}");
+        let err = runtime.block_on(client.list_connectors()).unwrap_err();
+        assert!(matches!(err, KofrError::Auth(_)), "{err:?}");
+        missing.assert();
+        rejected.assert();
+    }
+}
@@ -1,14 +1,32 @@
 use std::collections::HashMap;
+use std::fmt::Display;
 use std::path::PathBuf;
+use std::str::FromStr;
+use std::time::{Duration, Instant};
 
 use anyhow::{anyhow, ensure, Context, Ok, Result};
 use clap::{Args, Parser, Subcommand};
 use clap_stdin::FileOrStdin;
-use tabled::{settings::Style, Table};
+use regex::Regex;
+use wildmatch::WildMatch;
+
+use kofr::{
+    config::{ClusterContext, RetryPolicy},
+    connect::{
+        ConnectorConfig, ConnectorName, ConnectorStatus, ConnectorType, CreateConnector,
+        DescribeConnector, HTTPClient, Retrying, State, VerboseConnector,
+    },
+    connector_plugins::ConfigValidation,
+    error::KofrError,
+    history::History,
+    loggers::Scope,
+    offsets::ConnectorOffsets,
+    tasks::TaskState,
+};
 
 use crate::{
-    config::ClusterContext,
-    connect::{ConnectorConfig, CreateConnector, DescribeConnector, HTTPClient},
+    manifest::{ExportedConnector, ManifestFormat},
+    output::{Outcome, Output},
 };
 
 /// Kafka Connect CLI for connect cluster management
@@ -20,6 +38,12 @@ pub struct Cli {
     /// config file (default is $HOME/.kofr/config)
     #[arg(long = "config-file")]
     pub config_file: Option<PathBuf>,
+    /// output format, lists default to table and single resources to json
+    #[arg(short = 'o', long = "output", global = true, value_enum)]
+    pub output: Option<Output>,
+    /// fail right away instead of retrying requests while the cluster rebalances or a host is down, other hosts are still tried once
+    #[arg(long = "no-retry", global = true)]
+    pub no_retry: bool,
 }
 
 #[derive(Subcommand, Debug)]
@@ -33,6 +57,15 @@ pub enum Action {
     #[clap(name = "connector", alias = "cn")]
     ConnectorAction(ConnectorAction),
 
+    /// create or update connectors from a file or a directory of connector definitions, like kubectl apply
+    Apply(Apply),
+
+    /// show differences between local connector definitions and the live connectors configs, exits with 3 when they differ
+    Diff(Diff),
+
+    /// export every connector of the current cluster to a directory, one file per connector
+    Export(Export),
+
     /// Handle kofr configuration
     #[command(subcommand)]
     #[clap(name = "config")]
@@ -56,10 +89,104 @@ pub enum Action {
     #[command(subcommand)]
     #[clap(alias = "plugins")]
     Plugin(Plugin),
+
+    /// get or change workers log levels at runtime
+    #[command(subcommand)]
+    #[clap(alias = "loggers")]
+    Logger(Logger),
+}
+
+#[derive(Args, Debug)]
+pub struct List {
+    /// only list connectors whose name matches this glob, e.g. "orders-*"
+    pub name: Option<String>,
+    /// only list connectors whose name matches this regular expression
+    #[arg(long = "regex", conflicts_with = "name")]
+    pub regex: Option<Regex>,
+    /// only list connectors in one of these states, e.g. FAILED,PAUSED
+    #[arg(long = "state", value_parser = parse_state, value_delimiter = ',')]
+    pub state: Vec<State>,
+    /// only list connectors of this type
+    #[arg(long = "type", value_parser = parse_connector_type)]
+    pub connector_type: Option<ConnectorType>,
+    /// only list connectors running on this worker, e.g. 10.0.0.1:8083
+    #[arg(long = "worker")]
+    pub worker: Option<String>,
+    /// sort connectors by this column
+    #[arg(long = "sort-by", value_enum)]
+    pub sort_by: Option<SortBy>,
+    /// poll and redraw every interval (default 2s), highlighting rows whose state changed
+    #[arg(short = 'w', long = "watch", num_args = 0..=1, default_missing_value = "2s", value_parser = humantime::parse_duration)]
+    pub watch: Option<Duration>,
+}
+
+/// Column to sort the connectors list by, ties are broken by name
+#[derive(clap::ValueEnum, Debug, Clone, Copy, PartialEq)]
+pub enum SortBy {
+    Name,
+    State,
+    Tasks,
+    Worker,
+}
+
+impl SortBy {
+    pub fn sort(self, connectors: &mut [VerboseConnector]) {
+        connectors.sort_by(|a, b| {
+            let by_column = match self {
+                Self::Name => std::cmp::Ordering::Equal,
+                Self::State => a.state.to_string().cmp(&b.state.to_string()),
+                Self::Tasks => a.tasks.cmp(&b.tasks),
+                Self::Worker => a.worker_id.cmp(&b.worker_id),
+            };
+            by_column.then_with(|| a.name.0.cmp(&b.name.0))
+        });
+    }
 }
 
 #[derive(Args, Debug)]
-pub struct List {}
+pub struct Apply {
+    /// connector definition file, directory of .json or .yaml files, or - to read from stdin
+    #[arg(short = 'f', long = "file")]
+    pub path: PathBuf,
+    /// maximum number of requests sent at once
+    #[arg(long = "concurrency", default_value_t = crate::parallel::DEFAULT_CONCURRENCY, value_parser = clap::builder::RangedU64ValueParser::<usize>::new().range(1..))]
+    pub concurrency: usize,
+    /// apply definitions even if they hold values redacted by export
+    #[arg(long = "allow-redacted")]
+    pub allow_redacted: bool,
+}
+
+#[derive(Args, Debug)]
+pub struct Export {
+    /// directory to write connector definitions to, created if it does not exist
+    #[arg(short = 'd', long = "dir")]
+    pub dir: PathBuf,
+    /// format of the written connector definitions
+    #[arg(long = "format", value_enum, default_value_t = ManifestFormat::Json)]
+    pub format: ManifestFormat,
+    /// replace values of config keys that look like credentials, e.g. passwords and tokens
+    #[arg(long = "redact-secrets")]
+    pub redact_secrets: bool,
+    /// include a snapshot of each connector's status, ignored when applying the files back
+    #[arg(long = "include-status")]
+    pub include_status: bool,
+    /// maximum number of requests sent at once
+    #[arg(long = "concurrency", default_value_t = crate::parallel::DEFAULT_CONCURRENCY, value_parser = clap::builder::RangedU64ValueParser::<usize>::new().range(1..))]
+    pub concurrency: usize,
+}
+
+#[derive(Args, Debug)]
+pub struct Diff {
+    /// connector definition file, directory of .json or .yaml files, or - to read from stdin
+    #[arg(short = 'f', long = "file")]
+    pub path: PathBuf,
+    /// print a unified diff of the configs instead of a key level diff
+    #[arg(short = 'u', long = "unified")]
+    pub unified: bool,
+    /// maximum number of requests sent at once
+    #[arg(long = "concurrency", default_value_t = crate::parallel::DEFAULT_CONCURRENCY, value_parser = clap::builder::RangedU64ValueParser::<usize>::new().range(1..))]
+    pub concurrency: usize,
+}
 
 #[derive(Subcommand, Debug)]
 pub enum ConfigAction {
@@ -120,18 +247,39 @@ pub enum ConnectorAction {
     Pause(Pause),
     /// resume a paused connector or do nothing of the connector is not paused
     Resume(Resume),
+    /// stop the connector and shut down its tasks, keeping its configuration
+    Stop(Stop),
     /// restar the connector, you may use --include-tasks and/or --only-failed to restart any combination of the Connector and/or Task instances for the connector.
     Restart(Restart),
     /// delete a connector, halting all tasks and deleting its configuration.
     Delete(Delete),
-    /// patches a connector configuration with provided
+    /// patch a connector's config with a JSON merge patch, where null removes a key, or an array of JSON Patch operations, keeping the keys the patch does not touch
     Patch(Patch),
+    /// set config keys of a connector, e.g. 'kofr cn set my-sink tasks.max=8 batch.size=500'
+    Set(SetConfig),
+    /// remove config keys of a connector
+    Unset(UnsetConfig),
+    /// list the configs a connector had before kofr updated or deleted it, kept under ~/.kofr/history
+    History(ConnectorHistory),
+    /// restore a config of a connector from its history, recreating the connector if it was deleted
+    Rollback(Rollback),
+    /// copy connectors between clusters of the configuration, the name may be a glob pattern like 'jdbc-*' to copy many
+    Copy(CopyConnector),
+    /// block until a connector reaches a state. exits with 75 on timeout and 70 when the connector or one of its tasks failed
+    Wait(Wait),
+    /// get, alter or reset a connector's offsets, the connector must be stopped before altering or resetting them
+    #[command(subcommand)]
+    Offsets(Offsets),
 }
 
 #[derive(Args, Debug)]
 pub struct Create {
+    /// connector definition file, .json or .yaml, or - to read from stdin. A YAML file may hold many definitions separated by ---
     #[arg(short = 'f', long = "file")]
-    pub config: FileOrStdin,
+    pub path: PathBuf,
+    /// create connectors even if their definitions hold values redacted by export
+    #[arg(long = "allow-redacted")]
+    pub allow_redacted: bool,
 }
 
 #[derive(Args, Debug)]
@@ -142,11 +290,17 @@ pub struct Describe {
 #[derive(Args, Debug)]
 pub struct Edit {
     name: String,
+    /// format of the config opened in the editor
+    #[arg(long = "format", value_enum, default_value_t)]
+    format: ManifestFormat,
 }
 
 #[derive(Args, Debug)]
 pub struct Status {
     pub name: String,
+    /// poll and redraw every interval (default 2s), highlighting rows whose state changed
+    #[arg(short = 'w', long = "watch", num_args = 0..=1, default_missing_value = "2s", value_parser = humantime::parse_duration)]
+    pub watch: Option<Duration>,
 }
 
 #[derive(Args, Debug)]
@@ -164,6 +318,11 @@ pub struct Resume {
     pub name: String,
 }
 
+#[derive(Args, Debug)]
+pub struct Stop {
+    pub name: String,
+}
+
 #[derive(Args, Debug)]
 pub struct Delete {
     pub name: String,
@@ -172,8 +331,168 @@ pub struct Delete {
 #[derive(Args, Debug)]
 pub struct Patch {
     pub name: String,
+    /// e.g. '{"tasks.max": "4", "batch.size": null}' or '[{"op": "remove", "path": "/batch.size"}]'
     #[arg(short = 'd', long = "data")]
     pub data: String,
+    /// show the changes without applying them
+    #[arg(long = "dry-run")]
+    pub dry_run: bool,
+}
+
+#[derive(Args, Debug)]
+pub struct SetConfig {
+    pub name: String,
+    #[arg(value_name = "KEY=VALUE", required = true, value_parser = parse_key_value)]
+    pub pairs: Vec<(String, String)>,
+    /// validate the updated config with the connector's plugin before applying it
+    #[arg(long = "validate")]
+    pub validate: bool,
+    /// show the changes without applying them
+    #[arg(long = "dry-run")]
+    pub dry_run: bool,
+}
+
+#[derive(Args, Debug)]
+pub struct UnsetConfig {
+    pub name: String,
+    #[arg(value_name = "KEY", required = true)]
+    pub keys: Vec<String>,
+    /// validate the updated config with the connector's plugin before applying it
+    #[arg(long = "validate")]
+    pub validate: bool,
+    /// show the changes without applying them
+    #[arg(long = "dry-run")]
+    pub dry_run: bool,
+}
+
+#[derive(Args, Debug)]
+pub struct ConnectorHistory {
+    pub name: String,
+}
+
+#[derive(Args, Debug)]
+pub struct Rollback {
+    pub name: String,
+    /// revision to restore as listed by history, defaults to the latest one
+    #[arg(long = "to")]
+    pub to: Option<usize>,
+    /// show the changes without applying them
+    #[arg(long = "dry-run")]
+    pub dry_run: bool,
+}
+
+#[derive(Args, Debug)]
+pub struct CopyConnector {
+    /// connector name, or a glob pattern matching many connectors
+    pub name: String,
+    /// cluster to copy connectors from, defaults to the current cluster
+    #[arg(long = "from")]
+    pub from: Option<String>,
+    /// cluster to copy connectors to
+    #[arg(long = "to")]
+    pub to: String,
+    /// name of the copied connector on the target cluster, only valid when copying a single connector
+    #[arg(long = "rename")]
+    pub rename: Option<String>,
+    /// override a config key of the copied connectors, may be repeated
+    #[arg(long = "set", value_name = "KEY=VALUE", value_parser = parse_key_value)]
+    pub overrides: Vec<(String, String)>,
+    /// update connectors that already exist on the target cluster instead of failing
+    #[arg(long = "overwrite")]
+    pub overwrite: bool,
+    /// print what would be copied without changing the target cluster
+    #[arg(long = "dry-run")]
+    pub dry_run: bool,
+    /// maximum number of requests sent at once
+    #[arg(long = "concurrency", default_value_t = crate::parallel::DEFAULT_CONCURRENCY, value_parser = clap::builder::RangedU64ValueParser::<usize>::new().range(1..))]
+    pub concurrency: usize,
+}
+
+#[derive(Args, Debug)]
+pub struct Wait {
+    pub name: String,
+    /// state to wait for
+    #[arg(long = "for", value_enum, default_value_t = WaitFor::Running)]
+    pub state: WaitFor,
+    /// give up after this long, e.g. 30s or 5m
+    #[arg(long = "timeout", default_value = "5m", value_parser = humantime::parse_duration)]
+    pub timeout: Duration,
+    /// time between two status polls
+    #[arg(long = "interval", default_value = "2s", value_parser = humantime::parse_duration)]
+    pub interval: Duration,
+}
+
+/// State `connector wait` blocks until
+#[derive(clap::ValueEnum, Debug, Clone, Copy, PartialEq)]
+pub enum WaitFor {
+    /// the connector and all of its tasks are running, never reached by connectors without tasks
+    Running,
+    /// the connector and all of its tasks are paused
+    Paused,
+    /// the connector is stopped
+    Stopped,
+    /// the connector no longer exists
+    Deleted,
+}
+
+impl WaitFor {
+    /// whether the connector reached this state, a connector without tasks is not running yet
+    /// as tasks are assigned shortly after the connector itself starts
+    pub fn reached(self, status: &ConnectorStatus) -> bool {
+        let all_tasks = |state: TaskState| status.tasks.iter().all(|t| t.state == state);
+        match self {
+            Self::Running => {
+                status.connector_state.state == State::Running
+                    && !status.tasks.is_empty()
+                    && all_tasks(TaskState::Running)
+            }
+            Self::Paused => {
+                status.connector_state.state == State::Paused && all_tasks(TaskState::Paused)
+            }
+            Self::Stopped => status.connector_state.state == State::Stopped,
+            Self::Deleted => false,
+        }
+    }
+}
+
+impl Display for WaitFor {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        match self {
+            Self::Running => write!(f, "running"),
+            Self::Paused => write!(f, "paused"),
+            Self::Stopped => write!(f, "stopped"),
+            Self::Deleted => write!(f, "deleted"),
+        }
+    }
+}
+
+#[derive(Subcommand, Debug)]
+pub enum Offsets {
+    /// get the current offsets of a connector
+    Get(OffsetsGet),
+    /// alter the offsets of given partitions, read from file or stdin. a null offset resets the partition
+    #[clap(alias = "alter")]
+    Set(OffsetsSet),
+    /// reset all offsets of a connector
+    Reset(OffsetsReset),
+}
+
+#[derive(Args, Debug)]
+pub struct OffsetsGet {
+    pub name: String,
+}
+
+#[derive(Args, Debug)]
+pub struct OffsetsSet {
+    pub name: String,
+    /// partitions and offsets in the same format returned by the offsets get endpoint
+    #[arg(short = 'f', long = "file")]
+    pub offsets: FileOrStdin,
+}
+
+#[derive(Args, Debug)]
+pub struct OffsetsReset {
+    pub name: String,
 }
 
 #[derive(Args, Debug)]
@@ -206,6 +525,12 @@ pub enum Task {
 #[derive(Args, Debug)]
 pub struct TaskList {
     pub connector_name: String,
+    /// poll and redraw every interval (default 2s), highlighting rows whose state changed
+    #[arg(short = 'w', long = "watch", num_args = 0..=1, default_missing_value = "2s", value_parser = humantime::parse_duration)]
+    pub watch: Option<Duration>,
+    /// maximum number of requests sent at once
+    #[arg(long = "concurrency", default_value_t = crate::parallel::DEFAULT_CONCURRENCY, value_parser = clap::builder::RangedU64ValueParser::<usize>::new().range(1..))]
+    pub concurrency: usize,
 }
 
 #[derive(Args, Debug)]
@@ -264,131 +589,1029 @@ pub struct ValidateConfig {
     pub config: FileOrStdin,
 }
 
+#[derive(Subcommand, Debug)]
+pub enum Logger {
+    /// list the levels of all loggers that were explicitly set or modified
+    #[clap(alias = "ls")]
+    List(LoggerList),
+
+    /// get the level of a logger
+    Get(LoggerGet),
+
+    /// set the level of a logger and its children
+    Set(LoggerSet),
+}
+
+#[derive(Args, Debug)]
+pub struct LoggerList {
+    /// talk to this host of the current cluster instead of the first available one
+    #[arg(long = "host")]
+    pub host: Option<String>,
+}
+
+#[derive(Args, Debug)]
+pub struct LoggerGet {
+    /// logger name, e.g. org.apache.kafka.connect.runtime.WorkerSinkTask
+    pub name: String,
+    /// talk to this host of the current cluster instead of the first available one
+    #[arg(long = "host")]
+    pub host: Option<String>,
+}
+
+#[derive(Args, Debug)]
+pub struct LoggerSet {
+    /// logger name, e.g. org.apache.kafka.connect.runtime.WorkerSinkTask
+    pub name: String,
+    /// log4j level, one of: OFF, FATAL, ERROR, WARN, INFO, DEBUG and TRACE
+    pub level: String,
+    /// apply the level to the contacted worker only or to every worker in the cluster
+    #[arg(long = "scope", value_enum, default_value_t = Scope::Worker)]
+    pub scope: Scope,
+    /// talk to this host of the current cluster instead of the first available one
+    #[arg(long = "host")]
+    pub host: Option<String>,
+}
+
 impl List {
-    pub fn run(self, connect_client: HTTPClient) -> Result<()> {
+    pub fn run(self, connect_client: HTTPClient, output: Option<Output>) -> Result<()> {
+        if let Some(interval) = self.watch {
+            let wide = watch_wide(output)?;
+            return crate::watch::watch("kofr list", interval, wide, || {
+                self.fetch(&connect_client)
+            });
+        }
+        let connectors = self.fetch(&connect_client)?;
+        crate::output::print(&connectors, output.unwrap_or(Output::Table))
+    }
+
+    fn fetch(&self, connect_client: &HTTPClient) -> Result<Vec<VerboseConnector>> {
+        let mut connectors = connect_client.list_connectors_status()?;
+        connectors.retain(|connector| self.matches(connector));
+        if let Some(sort_by) = self.sort_by {
+            sort_by.sort(&mut connectors);
+        }
+        Ok(connectors)
+    }
+
+    fn matches(&self, connector: &VerboseConnector) -> bool {
+        let name = &connector.name.0;
+        self.name
+            .as_ref()
+            .is_none_or(|pattern| WildMatch::new(pattern).matches(name))
+            && self.regex.as_ref().is_none_or(|regex| regex.is_match(name))
+            && (self.state.is_empty() || self.state.contains(&connector.state))
+            && self
+                .connector_type
+                .as_ref()
+                .is_none_or(|t| t == &connector.connector_type)
+            && self
+                .worker
+                .as_ref()
+                .is_none_or(|worker| worker == &connector.worker_id)
+    }
+}
+
+impl Apply {
+    pub fn run(self, connect_client: HTTPClient, output: Option<Output>) -> Result<()> {
+        let connectors = crate::manifest::read_manifests(&self.path)?;
+        if !self.allow_redacted {
+            crate::manifest::ensure_not_redacted(&connectors)?;
+        }
+        let existing = connect_client.list_connectors()?;
+
+        let applied = crate::parallel::map(&connectors, self.concurrency, |connector| {
+            apply_connector(
+                &connect_client,
+                connector,
+                existing.contains(&connector.name.0),
+            )
+        });
+        let mut outcomes = Vec::new();
+        let mut failed = 0;
+        for (connector, applied) in connectors.iter().zip(applied) {
+            let name = &connector.name.0;
+            match applied {
+                Result::Ok(outcome) => outcomes.push(Outcome::new(
+                    name,
+                    format!("connector/{} {}", name, outcome),
+                )),
+                Err(e) => {
+                    eprintln!("error applying connector/{}: {:#}", name, e);
+                    failed += 1;
+                }
+            }
+        }
+        crate::output::print_outcomes(&outcomes, output)?;
+        ensure!(
+            failed == 0,
+            "failed applying {} out of {} connectors",
+            failed,
+            connectors.len()
+        );
+        Ok(())
+    }
+}
+
+fn apply_connector(
+    connect_client: &HTTPClient,
+    connector: &CreateConnector,
+    exists: bool,
+) -> Result<&'static str> {
+    if !exists {
+        connect_client.create_connector(connector)?;
+        return Ok("created");
+    }
+    let desired_config = crate::manifest::desired_config(connector);
+    let current_config = connect_client.get_connector_config(&connector.name.0)?;
+    if current_config == desired_config {
+        return Ok("unchanged");
+    }
+    connect_client.put_connector(&connector.name.0, desired_config)?;
+    Ok("configured")
+}
+
+/// Exit code of `kofr diff` when connectors drifted, apart from the 1 of failed runs and the 2
+/// of usage errors
+pub const DRIFTED: exitcode::ExitCode = 3;
+
+impl Diff {
+    /// prints the drift of every connector, returns the process exit code, `DRIFTED` when any
+    /// drift was found. Connectors whose live config could not be read are reported and fail
+    /// the run once the others were printed.
+    pub fn run(
+        self,
+        connect_client: HTTPClient,
+        output: Option<Output>,
+    ) -> Result<exitcode::ExitCode> {
+        let connectors = crate::manifest::read_manifests(&self.path)?;
+        let existing = connect_client.list_connectors()?;
+        let color = crate::diff::use_color();
+
+        let live_configs = crate::parallel::map(&connectors, self.concurrency, |connector| {
+            match existing.contains(&connector.name.0) {
+                true => connect_client.get_connector_config(&connector.name.0),
+                false => Result::Ok(ConnectorConfig::new()),
+            }
+        });
+
+        // unified diffs are only printed by table outputs, the others print the changed keys
+        let unified = self.unified && matches!(output, None | Some(Output::Table | Output::Wide));
+        let mut drifts = Vec::new();
+        let mut failed = 0;
+        for (connector, live_config) in connectors.iter().zip(live_configs) {
+            let name = &connector.name.0;
+            let local_config = crate::manifest::desired_config(connector);
+            let live_config = match live_config {
+                Result::Ok(live_config) => live_config,
+                Err(e) => {
+                    eprintln!("error diffing connector/{}: {:#}", name, e);
+                    failed += 1;
+                    continue;
+                }
+            };
+
+            let diff = crate::diff::ConfigDiff::new(&live_config, &local_config);
+            if diff.is_empty() {
+                continue;
+            }
+            if unified {
+                print!(
+                    "{}",
+                    crate::diff::unified(
+                        &live_config,
+                        &local_config,
+                        &format!("live/{}", name),
+                        &format!("local/{}", name),
+                        color
+                    )
+                );
+            }
+            let message = match existing.contains(name) {
+                true => format!("connector/{}", name),
+                false => format!("connector/{} (missing on the cluster)", name),
+            };
+            drifts.push(Outcome::new(name, message).with_changes(diff));
+        }
+        if !unified {
+            crate::output::print_outcomes(&drifts, output)?;
+        }
+        ensure!(
+            failed == 0,
+            "failed diffing {} out of {} connectors",
+            failed,
+            connectors.len()
+        );
+        match drifts.is_empty() {
+            true => Ok(exitcode::OK),
+            false => Ok(DRIFTED),
+        }
+    }
+}
+
+impl Export {
+    pub fn run(self, connect_client: HTTPClient, output: Option<Output>) -> Result<()> {
+        std::fs::create_dir_all(&self.dir).with_context(|| {
+            format!(
+                "failed creating directory \"{}\"",
+                self.dir.to_string_lossy()
+            )
+        })?;
+
         let connectors = connect_client.list_connectors_status()?;
-        let connectors_table = Table::new(connectors).with(Style::blank()).to_string();
-        println!("{}", connectors_table);
+        let exported = crate::parallel::map(&connectors, self.concurrency, |connector| {
+            self.export(&connect_client, &connector.name)
+        });
+        let mut outcomes = Vec::new();
+        let mut failed = 0;
+        for (connector, file) in connectors.iter().zip(exported) {
+            let name = &connector.name.0;
+            match file {
+                Result::Ok(file) => outcomes.push(Outcome::new(
+                    name,
+                    format!("exported connector/{} to {}", name, file.to_string_lossy()),
+                )),
+                Err(e) => {
+                    eprintln!("error exporting connector/{}: {:#}", name, e);
+                    failed += 1;
+                }
+            }
+        }
+        crate::output::print_outcomes(&outcomes, output)?;
+        if let None | Some(Output::Table | Output::Wide) = output {
+            println!(
+                "exported {} connectors to \"{}\"",
+                outcomes.len(),
+                self.dir.to_string_lossy()
+            );
+        }
+        ensure!(
+            failed == 0,
+            "failed exporting {} out of {} connectors",
+            failed,
+            connectors.len()
+        );
         Ok(())
     }
+
+    /// writes the definition of a connector to the export directory, returning the file path
+    fn export(&self, connect_client: &HTTPClient, name: &ConnectorName) -> Result<PathBuf> {
+        let mut config = connect_client.get_connector_config(&name.0)?;
+        if self.redact_secrets {
+            crate::manifest::redact_secrets(&mut config);
+        }
+        let status = match self.include_status {
+            true => Some(connect_client.get_connector_status(&name.0)?),
+            false => None,
+        };
+        let exported = ExportedConnector {
+            name: name.clone(),
+            config: config.into_iter().collect(),
+            status,
+        };
+
+        let file_name = format!("{}.{}", name.0.replace('/', "_"), self.format.extension());
+        let file = self.dir.join(file_name);
+        std::fs::write(&file, exported.render(self.format)?)
+            .with_context(|| format!("failed writing file \"{}\"", file.to_string_lossy()))?;
+        Ok(file)
+    }
 }
 
 impl Create {
-    pub fn run(self, connect_client: HTTPClient) -> Result<()> {
-        let create_connector = self.config;
-        let create_connector: CreateConnector = serde_json::from_str(&create_connector)?;
-        let response = connect_client.create_connector(&create_connector)?;
-        let response = serde_json::to_string_pretty(&response)?;
-        println!(
-            "successfully created connector: {}",
-            &create_connector.name.0
+    pub fn run(self, connect_client: HTTPClient, output: Option<Output>) -> Result<()> {
+        let connectors = crate::manifest::read_manifests(&self.path)?;
+        if !self.allow_redacted {
+            crate::manifest::ensure_not_redacted(&connectors)?;
+        }
+        let mut failed = 0;
+        for create_connector in &connectors {
+            let response = match connect_client.create_connector(create_connector) {
+                Result::Ok(response) => response,
+                Err(e) => {
+                    eprintln!(
+                        "error creating connector/{}: {:#}",
+                        create_connector.name.0, e
+                    );
+                    failed += 1;
+                    continue;
+                }
+            };
+            if output.is_none() {
+                println!(
+                    "successfully created connector: {}",
+                    &create_connector.name.0
+                );
+            }
+            crate::output::print_document(
+                &response,
+                &response.name.0,
+                output.unwrap_or(Output::Json),
+            )?;
+        }
+        ensure!(
+            failed == 0,
+            "failed creating {} out of {} connectors",
+            failed,
+            connectors.len()
         );
-        println!("{}", response);
         Ok(())
     }
 }
 
 impl Patch {
-    pub fn run(self, connect_client: HTTPClient) -> Result<()> {
-        let new_config = self.data;
-        let new_config = serde_json::from_str(&new_config).context("invalid config format")?;
-        connect_client.put_connector(&self.name, new_config)?;
-        println!("successfully patched connector: '{}'", &self.name);
-        Ok(())
+    pub fn run(self, connect_client: HTTPClient, output: Option<Output>) -> Result<()> {
+        let current_config = connect_client.get_connector_config(&self.name)?;
+        let new_config = crate::patch::apply(&current_config, &self.data)?;
+        let update = ConfigUpdate {
+            validate: false,
+            dry_run: self.dry_run,
+        };
+        let message = format!("successfully patched connector: '{}'", &self.name);
+        update.apply(
+            &connect_client,
+            &self.name,
+            &current_config,
+            new_config,
+            output,
+            &message,
+        )
+    }
+}
+
+impl SetConfig {
+    pub fn run(self, connect_client: HTTPClient, output: Option<Output>) -> Result<()> {
+        let update = ConfigUpdate {
+            validate: self.validate,
+            dry_run: self.dry_run,
+        };
+        update.edit(&connect_client, &self.name, output, |config| {
+            config.extend(self.pairs);
+            Ok(())
+        })
     }
 }
 
+impl UnsetConfig {
+    pub fn run(self, connect_client: HTTPClient, output: Option<Output>) -> Result<()> {
+        let update = ConfigUpdate {
+            validate: self.validate,
+            dry_run: self.dry_run,
+        };
+        update.edit(&connect_client, &self.name, output, |config| {
+            for key in &self.keys {
+                config
+                    .remove(key)
+                    .ok_or_else(|| KofrError::not_found("config key", key))?;
+            }
+            Ok(())
+        })
+    }
+}
+
+impl ConnectorHistory {
+    pub fn run(self, history: Option<&History>, output: Option<Output>) -> Result<()> {
+        let history = history.context("could not get user's home dir")?;
+        let snapshots = history.snapshots(&self.name)?;
+        crate::output::print(&snapshots, output.unwrap_or(Output::Table))
+    }
+}
+
+impl Rollback {
+    pub fn run(
+        self,
+        connect_client: HTTPClient,
+        history: Option<&History>,
+        output: Option<Output>,
+    ) -> Result<()> {
+        let history = history.context("could not get user's home dir")?;
+        let snapshot = history.snapshot(&self.name, self.to)?;
+        // putting the config of a deleted connector creates it again
+        let current_config = match connect_client.get_connector_config(&self.name) {
+            Result::Ok(config) => config,
+            Err(KofrError::NotFound { .. }) => ConnectorConfig::new(),
+            Err(err) => return Err(err.into()),
+        };
+        let update = ConfigUpdate {
+            validate: false,
+            dry_run: self.dry_run,
+        };
+        let message = format!(
+            "connector: '{}' rolled back to revision {}",
+            &self.name, snapshot.revision
+        );
+        update.apply(
+            &connect_client,
+            &self.name,
+            &current_config,
+            snapshot.config,
+            output,
+            &message,
+        )
+    }
+}
+
+/// How the commands editing a connector's config in place apply their changes
+struct ConfigUpdate {
+    validate: bool,
+    dry_run: bool,
+}
+
+impl ConfigUpdate {
+    /// edits the live config of a connector and applies the result
+    fn edit<F>(
+        &self,
+        connect_client: &HTTPClient,
+        name: &str,
+        output: Option<Output>,
+        edit: F,
+    ) -> Result<()>
+    where
+        F: FnOnce(&mut ConnectorConfig) -> Result<()>,
+    {
+        let current_config = connect_client.get_connector_config(name)?;
+        let mut new_config = current_config.clone();
+        edit(&mut new_config)?;
+        let message = format!("connector: '{}' configured", name);
+        self.apply(
+            connect_client,
+            name,
+            &current_config,
+            new_config,
+            output,
+            &message,
+        )
+    }
+
+    /// prints the changed keys, then validates and puts the new config and prints the given
+    /// message once the connector was updated. Json and yaml outputs print the changed keys
+    /// along with the message instead.
+    fn apply(
+        &self,
+        connect_client: &HTTPClient,
+        name: &str,
+        current_config: &ConnectorConfig,
+        new_config: ConnectorConfig,
+        output: Option<Output>,
+        message: &str,
+    ) -> Result<()> {
+        let diff = crate::diff::ConfigDiff::new(current_config, &new_config);
+        let structured = matches!(output, Some(Output::Json | Output::Yaml));
+        let outcome = |message: String, diff: crate::diff::ConfigDiff| match structured {
+            true => Outcome::new(name, message).with_changes(diff),
+            false => Outcome::new(name, message),
+        };
+        if diff.is_empty() {
+            let message = format!("connector: '{}' unchanged", name);
+            return crate::output::print_outcome(&outcome(message, diff), output);
+        }
+        if !structured && output != Some(Output::Name) {
+            print!("{}", diff.key_level(crate::diff::use_color()));
+        }
+        if self.validate {
+            validate(connect_client, &new_config)?;
+        }
+        if self.dry_run {
+            // table outputs already printed the keys that would change
+            if structured {
+                let message = format!("connector: '{}' not changed, dry run", name);
+                crate::output::print_outcome(&outcome(message, diff), output)?;
+            }
+            return Ok(());
+        }
+
+        connect_client.put_connector(name, new_config)?;
+        crate::output::print_outcome(&outcome(message.to_string(), diff), output)
+    }
+}
+
+/// validates a connector config with the plugin of its connector.class
+fn validate(connect_client: &HTTPClient, config: &ConnectorConfig) -> Result<()> {
+    let class_name = config.get("connector.class").ok_or(KofrError::Validation(
+        "no connector.class was provided".to_string(),
+    ))?;
+    let response = connect_client.validate_config(class_name, config.clone())?;
+    ensure_valid(&response)
+}
+
+fn ensure_valid(response: &ConfigValidation) -> Result<()> {
+    ensure!(
+        response.error_count == 0,
+        KofrError::Validation(format!(
+            "connector config has {} error(s):\n{}",
+            response.error_count,
+            response.errors().join("\n")
+        ))
+    );
+    Ok(())
+}
+
 impl Describe {
-    pub fn run(self, connect_client: HTTPClient) -> Result<()> {
-        let describe_connector: DescribeConnector = connect_client.desribe_connector(&self.name)?;
-        let pretty_json = serde_json::to_string_pretty(&describe_connector)?;
-        println!("{pretty_json}");
-        Ok(())
+    pub fn run(self, connect_client: HTTPClient, output: Option<Output>) -> Result<()> {
+        let describe_connector: DescribeConnector =
+            connect_client.describe_connector(&self.name)?;
+        crate::output::print_document(
+            &describe_connector,
+            &self.name,
+            output.unwrap_or(Output::Json),
+        )
     }
 }
 
 impl Edit {
-    pub fn run(self, connect_client: HTTPClient) -> Result<()> {
+    pub fn run(self, connect_client: HTTPClient, output: Option<Output>) -> Result<()> {
+        self.edit_with(&Editor::new(), &connect_client, output)
+    }
+
+    fn edit_with(
+        &self,
+        editor: &Editor,
+        connect_client: &HTTPClient,
+        output: Option<Output>,
+    ) -> Result<()> {
         let old_config_json: ConnectorConfig = connect_client.get_connector_config(&self.name)?;
-        let old_config = serde_json::to_string_pretty(&old_config_json)?;
         let file = tempfile::Builder::new()
             .prefix(&format!("{}-edit-", &self.name))
-            .suffix(".json")
+            .suffix(&format!(".{}", self.format.extension()))
             .tempfile()
             .context("could not create tempfile for editing")?;
 
-        let editor = Editor::new();
-        std::fs::write(file.path(), old_config).context("failed writing data to tempfile")?;
-        std::process::Command::new(&editor.name)
-            .arg(file.path())
-            .spawn()
-            .with_context(|| format!("unable to launch the editor: {}", editor.name))?
-            .wait()?;
-
-        let new_config = std::fs::read_to_string(file.path())?;
-        let new_config_json: ConnectorConfig = serde_json::from_str(&new_config)?;
-        if old_config_json == new_config_json {
-            println!("Edit cancelled, no changes were made");
-            return Ok(());
+        let mut content = crate::manifest::render_config(&old_config_json, self.format)?;
+        let mut last_error: Option<String> = None;
+        loop {
+            let header = last_error.as_deref().map(edit_header).unwrap_or_default();
+            std::fs::write(file.path(), format!("{}{}", header, content))
+                .context("failed writing data to tempfile")?;
+            editor.edit(file.path())?;
+
+            let edited = std::fs::read_to_string(file.path())?;
+            let edited = strip_comment_header(&edited);
+            if edited.trim().is_empty() {
+                let message = match last_error {
+                    Some(_) => format!(
+                        "Edit cancelled, a copy of your changes was saved to \"{}\"",
+                        keep_edit(file, &content)?.display()
+                    ),
+                    None => String::from("Edit cancelled, no changes were made"),
+                };
+                return crate::output::print_message(&message, &self.name, output);
+            }
+            // saving the rejected config again as is gives up on the edit
+            if let Some(error) = last_error.filter(|_| edited.trim() == content.trim()) {
+                let path = keep_edit(file, edited)?;
+                return Err(anyhow!(KofrError::Validation(error)).context(format!(
+                    "edit cancelled, a copy of your changes was saved to \"{}\"",
+                    path.display()
+                )));
+            }
+            content = edited.to_string();
+
+            match self.apply(connect_client, &old_config_json, &content) {
+                Result::Ok(true) => break,
+                Result::Ok(false) => {
+                    let message = "Edit cancelled, no changes were made";
+                    return crate::output::print_message(message, &self.name, output);
+                }
+                Err(err) if is_validation_error(&err) => last_error = Some(format!("{err:#}")),
+                Err(err) => {
+                    let path = keep_edit(file, &content)?;
+                    return Err(err.context(format!(
+                        "could not edit connector: '{}', a copy of your changes was saved to \"{}\"",
+                        &self.name,
+                        path.display()
+                    )));
+                }
+            }
         }
-        connect_client.put_connector(&self.name, new_config_json)?;
-        println!("connector: {} edited.", &self.name);
+
+        let message = format!("connector: {} edited.", &self.name);
+        crate::output::print_message(&message, &self.name, output)?;
         Ok(())
     }
+
+    /// validates the edited config and puts it, returns false when nothing was changed
+    fn apply(
+        &self,
+        connect_client: &HTTPClient,
+        old_config: &ConnectorConfig,
+        content: &str,
+    ) -> Result<bool> {
+        let new_config = crate::manifest::parse_config(content, self.format)
+            .map_err(|err| KofrError::Validation(format!("invalid config format: {err:#}")))?;
+        if &new_config == old_config {
+            return Ok(false);
+        }
+        validate(connect_client, &new_config)?;
+        connect_client.put_connector(&self.name, new_config)?;
+        Ok(true)
+    }
+}
+
+/// the comment written above a config reopened in the editor after it was rejected
+fn edit_header(error: &str) -> String {
+    let mut header = String::from(
+        "# Please edit the config below. Lines beginning with a '#' will be ignored,\n\
+         # and an empty file will abort the edit. The config was rejected with:\n#\n",
+    );
+    for line in error.lines() {
+        header.push_str(&format!("# {}\n", line));
+    }
+    header.push_str("#\n");
+    header
+}
+
+/// the edited text without the comment lines kofr wrote above it
+fn strip_comment_header(text: &str) -> &str {
+    let mut rest = text;
+    while rest.trim_start_matches([' ', '\t']).starts_with('#') {
+        rest = rest.split_once('\n').map_or("", |(_, rest)| rest);
+    }
+    rest
+}
+
+/// keeps a copy of a rejected edit after the temporary file is removed
+fn keep_edit(file: tempfile::NamedTempFile, content: &str) -> Result<PathBuf> {
+    std::fs::write(file.path(), content).context("failed writing data to tempfile")?;
+    let (_, path) = file.keep().context("could not keep the edited file")?;
+    Ok(path)
+}
+
+fn is_validation_error(err: &anyhow::Error) -> bool {
+    matches!(
+        err.downcast_ref::<KofrError>(),
+        Some(KofrError::Validation(_))
+    )
 }
 
 impl Status {
-    pub fn run(self, connect_client: HTTPClient) -> Result<()> {
+    pub fn run(self, connect_client: HTTPClient, output: Option<Output>) -> Result<()> {
+        if let Some(interval) = self.watch {
+            let wide = watch_wide(output)?;
+            let title = format!("kofr connector status {}", &self.name);
+            return crate::watch::watch(&title, interval, wide, || {
+                Ok(connect_client.get_connector_status(&self.name)?)
+            });
+        }
         let status = connect_client.get_connector_status(&self.name)?;
-        let status = serde_json::to_string_pretty(&status)?;
-        println!("{status}");
-        Ok(())
+        crate::output::print_document(&status, &self.name, output.unwrap_or(Output::Json))
     }
 }
 
 impl Config {
-    pub fn run(self, connect_client: HTTPClient) -> Result<()> {
+    pub fn run(self, connect_client: HTTPClient, output: Option<Output>) -> Result<()> {
         let config = connect_client.get_connector_config(&self.name)?;
-        let config = serde_json::to_string_pretty(&config)?;
-        println!("{config}");
-        Ok(())
+        crate::output::print(&config, output.unwrap_or(Output::Json))
     }
 }
 
 impl Pause {
-    pub fn run(self, connect_client: HTTPClient) -> Result<()> {
+    pub fn run(self, connect_client: HTTPClient, output: Option<Output>) -> Result<()> {
         connect_client.pause_connector(&self.name)?;
-        println!("connector: \"{}\" paused successfully", &self.name);
+        let message = format!("connector: \"{}\" paused successfully", &self.name);
+        crate::output::print_message(&message, &self.name, output)?;
         Ok(())
     }
 }
 
 impl Resume {
-    pub fn run(self, connect_client: HTTPClient) -> Result<()> {
+    pub fn run(self, connect_client: HTTPClient, output: Option<Output>) -> Result<()> {
         connect_client.resume_connector(&self.name)?;
-        println!("connector: \"{}\" resumed successfully", &self.name);
+        let message = format!("connector: \"{}\" resumed successfully", &self.name);
+        crate::output::print_message(&message, &self.name, output)?;
+        Ok(())
+    }
+}
+
+impl Stop {
+    pub fn run(self, connect_client: HTTPClient, output: Option<Output>) -> Result<()> {
+        connect_client.stop_connector(&self.name)?;
+        let message = format!("connector: \"{}\" stopped successfully", &self.name);
+        crate::output::print_message(&message, &self.name, output)?;
         Ok(())
     }
 }
 
 impl Restart {
-    pub fn run(self, connect_client: HTTPClient) -> Result<()> {
-        connect_client.restart_connector(&self.name, self.include_tasks, self.only_failed)?;
-        println!("connector: \"{}\" restarted sucessfully", &self.name);
+    pub fn run(self, connect_client: HTTPClient, output: Option<Output>) -> Result<()> {
+        let status =
+            connect_client.restart_connector(&self.name, self.include_tasks, self.only_failed)?;
+        if let Some(status) = status {
+            // restarts including tasks report the state of everything being restarted
+            return crate::output::print_document(
+                &status,
+                &self.name,
+                output.unwrap_or(Output::Json),
+            );
+        }
+        let message = format!("connector: \"{}\" restarted sucessfully", &self.name);
+        crate::output::print_message(&message, &self.name, output)?;
         Ok(())
     }
 }
 
 impl Delete {
-    pub fn run(self, connect_client: HTTPClient) -> Result<()> {
+    pub fn run(self, connect_client: HTTPClient, output: Option<Output>) -> Result<()> {
         connect_client.delete_connector(&self.name)?;
-        println!("connector: \"{}\" deleted", &self.name);
+        let message = format!("connector: \"{}\" deleted", &self.name);
+        crate::output::print_message(&message, &self.name, output)?;
         Ok(())
     }
 }
 
+impl Wait {
+    /// returns the process exit code, `exitcode::SOFTWARE` when the connector failed and
+    /// `exitcode::TEMPFAIL` when it did not reach the state in time
+    pub fn run(
+        self,
+        connect_client: HTTPClient,
+        output: Option<Output>,
+    ) -> Result<exitcode::ExitCode> {
+        let deadline = Instant::now() + self.timeout;
+        loop {
+            // the connector may not be created yet or its worker may be restarting,
+            // so errors are only reported once the timeout is reached
+            let last_seen = if self.state == WaitFor::Deleted {
+                match connect_client.list_connectors() {
+                    Err(e) => format!("{e:#}"),
+                    Result::Ok(connectors) if !connectors.contains(&self.name) => {
+                        let message = format!("connector: \"{}\" deleted", &self.name);
+                        crate::output::print_message(&message, &self.name, output)?;
+                        return Ok(exitcode::OK);
+                    }
+                    Result::Ok(_) => String::from("connector still exists"),
+                }
+            } else {
+                match connect_client.get_connector_status(&self.name) {
+                    Err(e) => format!("{e:#}"),
+                    Result::Ok(status) => {
+                        if self.state.reached(&status) {
+                            let message =
+                                format!("connector: \"{}\" is {}", &self.name, self.state);
+                            crate::output::print_message(&message, &self.name, output)?;
+                            return Ok(exitcode::OK);
+                        }
+                        let failures = status.failures();
+                        if self.state == WaitFor::Running && !failures.is_empty() {
+                            for failure in failures {
+                                eprintln!("{failure}");
+                            }
+                            return Ok(exitcode::SOFTWARE);
+                        }
+                        format!(
+                            "connector is {} with {} task(s)",
+                            status.connector_state.state,
+                            status.tasks.len()
+                        )
+                    }
+                }
+            };
+            if Instant::now() + self.interval > deadline {
+                eprintln!(
+                    "timed out after {} waiting for connector: \"{}\" to be {}, {}",
+                    humantime::format_duration(self.timeout),
+                    &self.name,
+                    self.state,
+                    last_seen
+                );
+                return Ok(exitcode::TEMPFAIL);
+            }
+            std::thread::sleep(self.interval);
+        }
+    }
+}
+
+impl CopyConnector {
+    pub fn run(
+        &self,
+        current_config: &kofr::config::Config,
+        no_retry: bool,
+        output: Option<Output>,
+    ) -> Result<()> {
+        let source = match &self.from {
+            Some(name) => current_config.cluster(name)?,
+            None => current_config.current_context()?,
+        };
+        let target = current_config.cluster(&self.to)?;
+        ensure!(
+            source.name != target.name || self.rename.is_some(),
+            "source and target clusters are the same, use --rename to copy a connector within a cluster"
+        );
+
+        let source_client = cluster_client(source, no_retry)?;
+        let target_client = cluster_client(target, no_retry)?
+            .with_history(kofr::history::History::for_cluster(&target.name));
+
+        let pattern = WildMatch::new(&self.name);
+        let names: Vec<String> = source_client
+            .list_connectors()?
+            .into_iter()
+            .filter(|name| pattern.matches(name))
+            .collect();
+        ensure!(
+            !names.is_empty(),
+            "no connectors matching \"{}\" were found on cluster \"{}\"",
+            self.name,
+            source.name
+        );
+        ensure!(
+            self.rename.is_none() || names.len() == 1,
+            "--rename can only be used when copying a single connector, \"{}\" matched {} connectors",
+            self.name,
+            names.len()
+        );
+
+        let existing = target_client.list_connectors()?;
+        let outcomes = crate::parallel::map(&names, self.concurrency, |name| {
+            self.copy(name, &source_client, &target_client, &existing)
+        });
+        let mut copied = Vec::new();
+        let mut failed = 0;
+        for (name, outcome) in names.iter().zip(outcomes) {
+            let target_name = self.rename.as_ref().unwrap_or(name);
+            let outcome = match outcome {
+                Result::Ok(outcome) => outcome,
+                Err(e) => {
+                    eprintln!("error copying connector/{}: {:#}", name, e);
+                    failed += 1;
+                    continue;
+                }
+            };
+            match outcome {
+                CopyOutcome::Skipped => eprintln!(
+                    "skipping connector/{}: already exists on cluster \"{}\", use --overwrite to update it",
+                    target_name, target.name
+                ),
+                CopyOutcome::DryRun { exists, diff } => {
+                    let message = format!(
+                        "connector/{} would be {} on cluster \"{}\" (dry run)",
+                        target_name,
+                        if exists { "configured" } else { "created" },
+                        target.name
+                    );
+                    copied.push(Outcome::new(target_name, message).with_changes(diff));
+                }
+                CopyOutcome::Copied => {
+                    let message = format!(
+                        "copied connector/{} from cluster \"{}\" to connector/{} on cluster \"{}\"",
+                        name, source.name, target_name, target.name
+                    );
+                    copied.push(Outcome::new(target_name, message));
+                }
+            }
+        }
+
+        crate::output::print_outcomes(&copied, output)?;
+        if let (false, None | Some(Output::Table | Output::Wide)) = (self.dry_run, output) {
+            println!("copied {} out of {} connectors", copied.len(), names.len());
+        }
+        ensure!(
+            failed == 0,
+            "failed copying {} out of {} connectors",
+            failed,
+            names.len()
+        );
+        Ok(())
+    }
+
+    fn copy(
+        &self,
+        name: &str,
+        source_client: &HTTPClient,
+        target_client: &HTTPClient,
+        existing: &[String],
+    ) -> Result<CopyOutcome> {
+        let target_name = self.rename.as_deref().unwrap_or(name);
+        let mut config = source_client.get_connector_config(name)?;
+        config.insert("name".to_string(), target_name.to_string());
+        for (key, value) in &self.overrides {
+            config.insert(key.to_string(), value.to_string());
+        }
+
+        let exists = existing.iter().any(|n| n == target_name);
+        if exists && !self.overwrite {
+            return Ok(CopyOutcome::Skipped);
+        }
+
+        if self.dry_run {
+            let current_config = match exists {
+                true => target_client.get_connector_config(target_name)?,
+                false => ConnectorConfig::new(),
+            };
+            let diff = crate::diff::ConfigDiff::new(&current_config, &config);
+            return Ok(CopyOutcome::DryRun { exists, diff });
+        }
+
+        if exists {
+            target_client.put_connector(target_name, config)?;
+        } else {
+            target_client.create_connector(&CreateConnector {
+                name: ConnectorName(target_name.to_string()),
+                config,
+            })?;
+        }
+        Ok(CopyOutcome::Copied)
+    }
+}
+
+/// What happened to a connector copied by `CopyConnector`
+enum CopyOutcome {
+    Skipped,
+    DryRun {
+        exists: bool,
+        diff: crate::diff::ConfigDiff,
+    },
+    Copied,
+}
+
+impl OffsetsGet {
+    pub fn run(self, connect_client: HTTPClient, output: Option<Output>) -> Result<()> {
+        let offsets = connect_client.get_connector_offsets(&self.name)?;
+        let output = output.unwrap_or(Output::Table);
+        if let Output::Table | Output::Wide = output {
+            println!("Offsets of connector: '{}'", &self.name);
+        }
+        crate::output::print(&offsets, output)
+    }
+}
+
+impl OffsetsSet {
+    pub fn run(self, connect_client: HTTPClient, output: Option<Output>) -> Result<()> {
+        let offsets = self.offsets;
+        let offsets: ConnectorOffsets =
+            serde_json::from_str(&offsets).context("invalid offsets format")?;
+        let response = connect_client.alter_connector_offsets(&self.name, &offsets)?;
+        crate::output::print_message(&response.message, &self.name, output)?;
+        Ok(())
+    }
+}
+
+impl OffsetsReset {
+    pub fn run(self, connect_client: HTTPClient, output: Option<Output>) -> Result<()> {
+        let response = connect_client.reset_connector_offsets(&self.name)?;
+        crate::output::print_message(&response.message, &self.name, output)?;
+        Ok(())
+    }
+}
+
+/// A cluster of the config file as printed by json and yaml outputs, leaving out its credentials
+#[derive(serde::Serialize)]
+struct ClusterSummary<'a> {
+    name: &'a str,
+    hosts: &'a [String],
+    current: bool,
+}
+
+impl<'a> ClusterSummary<'a> {
+    fn new(config: &kofr::config::Config, cluster: &'a ClusterContext) -> Self {
+        Self {
+            name: &cluster.name,
+            hosts: &cluster.hosts,
+            current: config.current_cluster.as_ref() == Some(&cluster.name),
+        }
+    }
+}
+
+/// prints the name of every cluster, json and yaml outputs also print their hosts
+pub fn get_clusters(config: &kofr::config::Config, output: Option<Output>) -> Result<()> {
+    match output {
+        Some(format @ (Output::Json | Output::Yaml)) => {
+            let clusters: Vec<ClusterSummary> = config
+                .clusters
+                .iter()
+                .map(|cluster| ClusterSummary::new(config, cluster))
+                .collect();
+            crate::output::print_document(&clusters, "", format)
+        }
+        _ => {
+            for cluster in &config.clusters {
+                println!("{}", cluster.name);
+            }
+            Ok(())
+        }
+    }
+}
+
+/// prints the name of the current cluster, json and yaml outputs also print its hosts
+pub fn current_context(config: &kofr::config::Config, output: Option<Output>) -> Result<()> {
+    let cluster = config.current_context()?;
+    match output {
+        Some(format @ (Output::Json | Output::Yaml)) => crate::output::print_document(
+            &ClusterSummary::new(config, cluster),
+            &cluster.name,
+            format,
+        ),
+        _ => {
+            println!("{}", cluster.name);
+            Ok(())
+        }
+    }
+}
+
 impl UseCluster {
-    pub fn run(&self, current_config: &mut crate::config::Config) -> Result<()> {
+    pub fn run(
+        &self,
+        current_config: &mut kofr::config::Config,
+        output: Option<Output>,
+    ) -> Result<()> {
         let clusters: Vec<&String> = current_config.clusters.iter().map(|c| &c.name).collect();
 
         ensure!(
@@ -402,13 +1625,17 @@ impl UseCluster {
             serde_yaml::to_string(&current_config).context("invalid config yaml format")?;
         std::fs::write(&current_config.file_path, updated_config_yaml)
             .context("failed writing file to filesystem")?;
-        println!("Switched to cluster \"{}\"", self.cluster);
-        Ok(())
+        let message = format!("Switched to cluster \"{}\"", self.cluster);
+        crate::output::print_message(&message, &self.cluster, output)
     }
 }
 
 impl AddCluster {
-    pub fn run(&self, current_config: &mut crate::config::Config) -> Result<()> {
+    pub fn run(
+        &self,
+        current_config: &mut kofr::config::Config,
+        output: Option<Output>,
+    ) -> Result<()> {
         let cluster_name = &self.name;
         let hosts: Vec<String> = self
             .hosts
@@ -429,6 +1656,7 @@ impl AddCluster {
         current_config.clusters.push(ClusterContext {
             name: cluster_name.to_string(),
             hosts,
+            ..Default::default()
         });
         current_config.current_cluster = Some(cluster_name.to_string());
 
@@ -436,13 +1664,17 @@ impl AddCluster {
             serde_yaml::to_string(&current_config).context("invalid config yaml format")?;
         std::fs::write(&current_config.file_path, updated_config_yaml)
             .context("failed writing file to filesystem")?;
-        println!("Added cluster \"{}\"", cluster_name);
-        Ok(())
+        let message = format!("Added cluster \"{}\"", cluster_name);
+        crate::output::print_message(&message, cluster_name, output)
     }
 }
 
 impl RemoveCluster {
-    pub fn run(&self, current_config: &mut crate::config::Config) -> Result<()> {
+    pub fn run(
+        &self,
+        current_config: &mut kofr::config::Config,
+        output: Option<Output>,
+    ) -> Result<()> {
         let index = current_config
             .clusters
             .iter()
@@ -458,66 +1690,79 @@ impl RemoveCluster {
             serde_yaml::to_string(&current_config).context("invalid config yaml format")?;
         std::fs::write(&current_config.file_path, updated_config_yaml)
             .context("failed writing file to filesystem")?;
-        println!("Removed cluster \"{}\"", self.name);
-        Ok(())
+        let message = format!("Removed cluster \"{}\"", self.name);
+        crate::output::print_message(&message, &self.name, output)
     }
 }
 
 impl Cluster {
-    pub fn run(&self, current_config: &crate::config::Config) -> Result<()> {
-        use crate::cluster::*;
+    pub fn run(&self, current_config: &kofr::config::Config, output: Option<Output>) -> Result<()> {
+        use kofr::cluster::*;
 
+        let current_context = current_config.current_context()?;
+        let agent = kofr::connect::cluster_agent(current_context)?;
         let mut hosts_status = Vec::<UriStatus>::new();
-        for host in &current_config.current_context()?.hosts {
-            hosts_status.push(get_uri_status(host));
+        for host in &current_context.hosts {
+            hosts_status.push(get_uri_status(&agent, host));
         }
         let mut _id = "";
         let cluster_id = hosts_status.iter().find(|&h| !h.id.is_empty());
         if let Some(cluster_id) = cluster_id {
             _id = &cluster_id.id;
         }
-        println!(
-            r#" Current Cluster: {}
+        let output = output.unwrap_or(Output::Table);
+        if let Output::Table | Output::Wide = output {
+            println!(
+                r#" Current Cluster: {}
  id : {}
  ..........................................."#,
-            current_config.current_cluster.as_ref().unwrap(),
-            _id
-        );
-        let status_table = Table::new(hosts_status).with(Style::blank()).to_string();
-        println!("{}", status_table);
-        Ok(())
+                current_config.current_cluster.as_ref().unwrap(),
+                _id
+            );
+        }
+        crate::output::print(&hosts_status, output)
     }
 }
 
 impl TaskList {
-    pub fn run(self, connect_host: &str) -> Result<()> {
-        let tasks_status: Result<Vec<crate::tasks::TaskStatus>> =
-            crate::tasks::list_tasks(connect_host, &self.connector_name)?
-                .iter()
-                .map(|t| crate::tasks::task_status(connect_host, &self.connector_name, t.id.task))
-                .collect();
+    pub fn run(self, connect_client: HTTPClient, output: Option<Output>) -> Result<()> {
+        if let Some(interval) = self.watch {
+            let wide = watch_wide(output)?;
+            let title = format!("kofr task list {}", &self.connector_name);
+            return crate::watch::watch(&title, interval, wide, || self.fetch(&connect_client));
+        }
+        let tasks_status = self.fetch(&connect_client)?;
+        let output = output.unwrap_or(Output::Table);
+        if let Output::Table | Output::Wide = output {
+            println!("Active tasks of connector: '{}'", &self.connector_name);
+        }
+        crate::output::print(&tasks_status, output)
+    }
 
-        let tasks_table = Table::new(tasks_status?).with(Style::blank()).to_string();
-        println!("Active tasks of connector: '{}'", &self.connector_name);
-        println!("{}", tasks_table);
-        Ok(())
+    fn fetch(&self, connect_client: &HTTPClient) -> Result<Vec<kofr::tasks::TaskStatus>> {
+        let tasks = connect_client.list_tasks(&self.connector_name)?;
+        let statuses = crate::parallel::map(&tasks, self.concurrency, |t| {
+            connect_client.task_status(&self.connector_name, t.id.task)
+        });
+        Ok(statuses.into_iter().collect::<kofr::error::Result<_>>()?)
     }
 }
 
 impl TaskRestart {
-    pub fn run(self, connect_host: &str) -> Result<()> {
-        crate::tasks::restart_task(connect_host, &self.connector_name, self.task_id)?;
-        println!(
+    pub fn run(self, connect_client: HTTPClient, output: Option<Output>) -> Result<()> {
+        connect_client.restart_task(&self.connector_name, self.task_id)?;
+        let message = format!(
             "restarted task: '{}/{}'",
             &self.connector_name, self.task_id
         );
+        crate::output::print_message(&message, &self.task_id.to_string(), output)?;
         Ok(())
     }
 }
 
 impl TaskStatus {
-    pub fn run(self, connect_host: &str) -> Result<()> {
-        let binding = crate::tasks::list_tasks(connect_host, &self.connector_name)?;
+    pub fn run(self, connect_client: HTTPClient, output: Option<Output>) -> Result<()> {
+        let binding = connect_client.list_tasks(&self.connector_name)?;
         let task_response = binding
             .iter()
             .find(|t| t.id.task == self.task_id && t.id.connector == self.connector_name)
@@ -527,50 +1772,48 @@ impl TaskStatus {
                 &self.task_id
             ))?;
 
-        let task_status =
-            crate::tasks::task_status(connect_host, &self.connector_name, self.task_id)?;
+        let task_status = connect_client.task_status(&self.connector_name, self.task_id)?;
 
         let task_status = serde_json::json!({
             "status": task_status,
             "config": task_response.config,
         });
-        let task_status = serde_json::to_string_pretty(&task_status)?;
-        println!("{}", task_status);
-        Ok(())
+        crate::output::print_document(
+            &task_status,
+            &self.task_id.to_string(),
+            output.unwrap_or(Output::Json),
+        )
     }
 }
 
 impl TopicList {
-    pub fn run(self, connect_host: &str) -> Result<()> {
-        let topics = crate::topics::list_topics(connect_host, &self.connector_name)?;
-        let topics = serde_json::to_string_pretty(&topics)?;
-        println!("{}", topics);
-        Ok(())
+    pub fn run(self, connect_client: HTTPClient, output: Option<Output>) -> Result<()> {
+        let topics = connect_client.list_topics(&self.connector_name)?;
+        crate::output::print(&topics, output.unwrap_or(Output::Table))
     }
 }
 
 impl TopicReset {
-    pub fn run(self, connect_host: &str) -> Result<()> {
-        crate::topics::reset(connect_host, &self.connector_name)?;
-        println!(
+    pub fn run(self, connect_client: HTTPClient, output: Option<Output>) -> Result<()> {
+        connect_client.reset_topics(&self.connector_name)?;
+        let message = format!(
             "resetted topics successfully of connector: '{}'",
             self.connector_name
         );
+        crate::output::print_message(&message, &self.connector_name, output)?;
         Ok(())
     }
 }
 
 impl PluginList {
-    pub fn run(self, connect_host: &str) -> Result<()> {
-        let plugins = crate::connector_plugins::list_plugins(connect_host)?;
-        let plugins = serde_json::to_string_pretty(&plugins)?;
-        println!("{}", plugins);
-        Ok(())
+    pub fn run(self, connect_client: HTTPClient, output: Option<Output>) -> Result<()> {
+        let plugins = connect_client.list_plugins()?;
+        crate::output::print(&plugins, output.unwrap_or(Output::Table))
     }
 }
 
 impl ValidateConfig {
-    pub fn run(self, connect_host: &str) -> Result<()> {
+    pub fn run(self, connect_client: HTTPClient, output: Option<Output>) -> Result<()> {
         let config = self.config;
         let config: HashMap<String, String> = serde_json::from_str(&config)?;
 
@@ -578,17 +1821,138 @@ impl ValidateConfig {
             Some(name) => name,
             None => config
                 .get("connector.class")
-                .ok_or(anyhow!("no connector.class was provided"))?
+                .ok_or(KofrError::Validation(
+                    "no connector.class was provided".to_string(),
+                ))?
                 .to_string(),
         };
 
-        let response =
-            crate::connector_plugins::validate_config(connect_host, &class_name, config)?;
-        let response = serde_json::to_string_pretty(&response)?;
-        println!("{}", response);
+        let response = connect_client.validate_config(&class_name, config)?;
+        crate::output::print_document(&response, &class_name, output.unwrap_or(Output::Json))?;
+        ensure_valid(&response)
+    }
+}
+impl Logger {
+    pub fn host(&self) -> Option<&str> {
+        match self {
+            Self::List(list) => list.host.as_deref(),
+            Self::Get(get) => get.host.as_deref(),
+            Self::Set(set) => set.host.as_deref(),
+        }
+    }
+}
+
+impl LoggerList {
+    pub fn run(self, connect_client: HTTPClient, output: Option<Output>) -> Result<()> {
+        let loggers = connect_client.list_loggers()?;
+        crate::output::print(&loggers, output.unwrap_or(Output::Table))
+    }
+}
+
+impl LoggerGet {
+    pub fn run(self, connect_client: HTTPClient, output: Option<Output>) -> Result<()> {
+        let level = connect_client.get_logger(&self.name)?;
+        crate::output::print_document(&level, &self.name, output.unwrap_or(Output::Json))
+    }
+}
+
+impl LoggerSet {
+    pub fn run(self, connect_client: HTTPClient, output: Option<Output>) -> Result<()> {
+        let level = self.level.to_uppercase();
+        let affected = connect_client.set_logger_level(&self.name, &level, self.scope)?;
+        let message = match self.scope {
+            Scope::Worker => format!("logger level set to {}", level),
+            Scope::Cluster => format!(
+                "requested level {} for logger: '{}' on all workers of the cluster",
+                level, &self.name
+            ),
+        };
+        if let Some(format @ (Output::Json | Output::Yaml)) = output {
+            let change = LoggerLevelChange {
+                name: &self.name,
+                level: &level,
+                message: &message,
+                loggers: &affected,
+            };
+            return crate::output::print_document(&change, &self.name, format);
+        }
+        match (self.scope, output) {
+            (Scope::Cluster, Some(Output::Name)) => println!("{}", self.name),
+            (Scope::Cluster, _) => println!("{}", message),
+            (Scope::Worker, Some(Output::Name)) => {}
+            (Scope::Worker, _) => println!("{} for loggers:", message),
+        }
+        for logger in affected {
+            println!("{}", logger);
+        }
         Ok(())
     }
 }
+
+/// A logger level change as printed by json and yaml outputs, a change to the whole cluster does
+/// not report the affected loggers
+#[derive(serde::Serialize)]
+struct LoggerLevelChange<'a> {
+    name: &'a str,
+    level: &'a str,
+    message: &'a str,
+    #[serde(skip_serializing_if = "<[String]>::is_empty")]
+    loggers: &'a [String],
+}
+
+/// whether a watched table shows wide columns, watching only supports table outputs
+fn watch_wide(output: Option<Output>) -> Result<bool> {
+    match output {
+        None | Some(Output::Table) => Ok(false),
+        Some(Output::Wide) => Ok(true),
+        Some(_) => Err(anyhow!("--watch only supports table and wide outputs")),
+    }
+}
+
+fn parse_state(s: &str) -> Result<State> {
+    State::from_str(&s.to_uppercase())
+}
+
+fn parse_connector_type(s: &str) -> Result<ConnectorType> {
+    ConnectorType::from_str(&s.to_lowercase())
+}
+
+/// a client of a cluster starting on its last healthy host, failing over to the other hosts and
+/// remembering where it ended up
+fn cluster_client(cluster: &ClusterContext, no_retry: bool) -> Result<HTTPClient> {
+    let http_agent = kofr::connect::cluster_agent(cluster)?;
+    let client = HTTPClient::from_config(kofr::connect::HTTPClientConfig {
+        connect_uri: cluster.healthy_host(&http_agent)?,
+        http_agent,
+    });
+    Ok(client
+        .with_retry(retry_policy(cluster, no_retry))
+        .with_retry_callback(report_retry)
+        .with_failover(cluster)
+        .with_host_cache())
+}
+
+/// the retry policy of a cluster, `None` when retries are disabled with `--no-retry`
+pub fn retry_policy(cluster: &ClusterContext, no_retry: bool) -> Option<RetryPolicy> {
+    match no_retry {
+        true => None,
+        false => Some(cluster.retry.clone().unwrap_or_default()),
+    }
+}
+
+/// tells the user why a command is waiting before a request is retried
+pub fn report_retry(retrying: &Retrying) {
+    eprintln!("{}", retrying);
+}
+
+fn parse_key_value(s: &str) -> Result<(String, String)> {
+    let (key, value) = s
+        .split_once('=')
+        .ok_or(anyhow!("invalid KEY=VALUE: no `=` found in \"{}\"", s))?;
+    ensure!(!key.is_empty(), "invalid KEY=VALUE: empty key in \"{}\"", s);
+    Ok((key.to_string(), value.to_string()))
+}
+
 struct Editor {
     name: String,
 }
@@ -597,6 +1961,16 @@ impl Editor {
     fn new() -> Self {
         Default::default()
     }
+
+    /// opens a file in the editor and waits until it exits
+    fn edit(&self, path: &std::path::Path) -> Result<()> {
+        std::process::Command::new(&self.name)
+            .arg(path)
+            .spawn()
+            .with_context(|| format!("unable to launch the editor: {}", self.name))?
+            .wait()?;
+        Ok(())
+    }
 }
 
 impl Default for Editor {
@@ -606,3 +1980,565 @@ impl Default for Editor {
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use mockito::{Matcher, Server};
+    use std::io::Write;
+
+    fn mock_client(server: &Server) -> HTTPClient {
+        HTTPClient::from_config(kofr::connect::HTTPClientConfig {
+            http_agent: ureq::agent(),
+            connect_uri: server.url(),
+        })
+    }
+
+    fn copy_command(name: &str) -> CopyConnector {
+        CopyConnector {
+            name: name.to_string(),
+            from: None,
+            to: "staging".to_string(),
+            rename: None,
+            overrides: Vec::new(),
+            overwrite: false,
+            dry_run: false,
+            concurrency: 1,
+        }
+    }
+
+    const SINK_CONFIG: &str =
+        r#"{"name": "users-sink", "connector.class": "FileStreamSink", "tasks.max": "1"}"#;
+
+    #[test]
+    fn test_copying_renames_and_overrides_keys() {
+        let mut source = Server::new();
+        source
+            .mock("GET", "/connectors/users-sink/config")
+            .with_body(SINK_CONFIG)
+            .create();
+        let mut target = Server::new();
+        let created = target
+            .mock("POST", "/connectors")
+            .match_body(Matcher::Json(serde_json::json!({
+                "name": "users-sink-v2",
+                "config": {
+                    "name": "users-sink-v2",
+                    "connector.class": "FileStreamSink",
+                    "tasks.max": "4"
+                }
+            })))
+            .with_status(201)
+            .with_body(r#"{"name": "users-sink-v2", "config": {}, "tasks": [], "type": "sink"}"#)
+            .create();
+
+        let mut command = copy_command("users-sink");
+        command.rename = Some("users-sink-v2".to_string());
+        command.overrides = vec![("tasks.max".to_string(), "4".to_string())];
+        let outcome = command
+            .copy(
+                "users-sink",
+                &mock_client(&source),
+                &mock_client(&target),
+                &[],
+            )
+            .unwrap();
+
+        assert!(matches!(outcome, CopyOutcome::Copied));
+        created.assert();
+    }
+
+    #[test]
+    fn test_copying_skips_existing_connectors_unless_overwritten() {
+        let mut source = Server::new();
+        source
+            .mock("GET", "/connectors/users-sink/config")
+            .with_body(SINK_CONFIG)
+            .expect(2)
+            .create();
+        let mut target = Server::new();
+        let updated = target
+            .mock("PUT", "/connectors/users-sink/config")
+            .with_body(r#"{"name": "users-sink", "config": {}, "tasks": [], "type": "sink"}"#)
+            .create();
+        let existing = ["users-sink".to_string()];
+
+        let mut command = copy_command("users-sink");
+        let outcome = command
+            .copy(
+                "users-sink",
+                &mock_client(&source),
+                &mock_client(&target),
+                &existing,
+            )
+            .unwrap();
+        assert!(matches!(outcome, CopyOutcome::Skipped));
+
+        command.overwrite = true;
+        let outcome = command
+            .copy(
+                "users-sink",
+                &mock_client(&source),
+                &mock_client(&target),
+                &existing,
+            )
+            .unwrap();
+        assert!(matches!(outcome, CopyOutcome::Copied));
+        updated.assert();
+    }
+
+    #[test]
+    fn test_copying_dry_run_leaves_the_target_untouched() {
+        let mut source = Server::new();
+        source
+            .mock("GET", "/connectors/users-sink/config")
+            .with_body(SINK_CONFIG)
+            .create();
+        let mut target = Server::new();
+        target
+            .mock("GET", "/connectors/users-sink/config")
+            .with_body(
+                r#"{"name": "users-sink", "connector.class": "FileStreamSink", "tasks.max": "2"}"#,
+            )
+            .create();
+        let changed = target
+            .mock("PUT", "/connectors/users-sink/config")
+            .expect(0)
+            .create();
+
+        let mut command = copy_command("users-sink");
+        command.overwrite = true;
+        command.dry_run = true;
+        let outcome = command
+            .copy(
+                "users-sink",
+                &mock_client(&source),
+                &mock_client(&target),
+                &["users-sink".to_string()],
+            )
+            .unwrap();
+
+        match outcome {
+            CopyOutcome::DryRun { exists, diff } => {
+                assert!(exists);
+                assert_eq!(diff.key_level(false), "~ tasks.max: 2 -> 1\n");
+            }
+            _ => panic!("expected a dry run"),
+        }
+        changed.assert();
+    }
+
+    #[test]
+    fn test_concurrency_must_be_positive() {
+        assert!(
+            Cli::try_parse_from(["kofr", "apply", "-f", "a.json", "--concurrency", "0"]).is_err()
+        );
+        let cli =
+            Cli::try_parse_from(["kofr", "apply", "-f", "a.json", "--concurrency", "2"]).unwrap();
+        assert!(matches!(
+            cli.command,
+            Action::Apply(Apply { concurrency: 2, .. })
+        ));
+    }
+
+    #[test]
+    fn test_failed_documents_do_not_stop_the_others() {
+        let mut server = Server::new();
+        let existing = server
+            .mock("POST", "/connectors")
+            .match_body(Matcher::PartialJsonString(
+                r#"{"name": "users-sink"}"#.to_string(),
+            ))
+            .with_status(409)
+            .with_body(r#"{"error_code": 409, "message": "Connector users-sink already exists"}"#)
+            .create();
+        let created = server
+            .mock("POST", "/connectors")
+            .match_body(Matcher::PartialJsonString(r#"{"name": "orders-sink"}"#.to_string()))
+            .with_status(201)
+            .with_body(r#"{"name": "orders-sink", "config": {"connector.class": "FileStreamSink"}, "tasks": [], "type": "sink"}"#)
+            .create();
+        let mut manifest = tempfile::Builder::new().suffix(".yaml").tempfile().unwrap();
+        write!(
+            manifest,
+            "name: users-sink\nconfig:\n  connector.class: FileStreamSink\n---\nname: orders-sink\nconfig:\n  connector.class: FileStreamSink\n"
+        )
+        .unwrap();
+
+        let create = Create {
+            path: manifest.path().to_path_buf(),
+            allow_redacted: false,
+        };
+        let err = create
+            .run(mock_client(&server), Some(Output::Name))
+            .unwrap_err();
+
+        assert_eq!(err.to_string(), "failed creating 1 out of 2 connectors");
+        existing.assert();
+        created.assert();
+    }
+
+    #[test]
+    fn test_setting_overwrites_and_adds_keys() {
+        let mut server = Server::new();
+        server
+            .mock("GET", "/connectors/users-sink/config")
+            .with_body(SINK_CONFIG)
+            .create();
+        let put = server
+            .mock("PUT", "/connectors/users-sink/config")
+            .match_body(Matcher::Json(serde_json::json!({
+                "name": "users-sink",
+                "connector.class": "FileStreamSink",
+                "tasks.max": "4",
+                "topics": "users"
+            })))
+            .with_body(r#"{"name": "users-sink", "config": {}, "tasks": [], "type": "sink"}"#)
+            .create();
+
+        let set = SetConfig {
+            name: "users-sink".to_string(),
+            pairs: vec![
+                ("tasks.max".to_string(), "4".to_string()),
+                ("topics".to_string(), "users".to_string()),
+            ],
+            validate: false,
+            dry_run: false,
+        };
+        set.run(mock_client(&server), Some(Output::Name)).unwrap();
+        put.assert();
+    }
+
+    #[test]
+    fn test_unsetting_a_missing_key_is_not_found() {
+        let mut server = Server::new();
+        server
+            .mock("GET", "/connectors/users-sink/config")
+            .with_body(SINK_CONFIG)
+            .create();
+        let put = server
+            .mock("PUT", "/connectors/users-sink/config")
+            .expect(0)
+            .create();
+
+        let unset = UnsetConfig {
+            name: "users-sink".to_string(),
+            keys: vec!["tasks.max".to_string(), "topics".to_string()],
+            validate: false,
+            dry_run: false,
+        };
+        let err = unset
+            .run(mock_client(&server), Some(Output::Name))
+            .unwrap_err();
+
+        assert!(matches!(
+            err.downcast_ref::<KofrError>(),
+            Some(KofrError::NotFound { .. })
+        ));
+        put.assert();
+    }
+
+    #[test]
+    fn test_validation_errors_block_the_put() {
+        let mut server = Server::new();
+        server
+            .mock("GET", "/connectors/users-sink/config")
+            .with_body(SINK_CONFIG)
+            .create();
+        let validated = server
+            .mock("PUT", "/connector-plugins/FileStreamSink/config/validate")
+            .with_body(
+                r#"{"name": "FileStreamSink", "error_count": 1, "groups": [], "configs": [
+                    {"definition": {"name": "tasks.max", "type": "INT", "required": true,
+                                    "default_value": null, "importance": "HIGH",
+                                    "documentation": null, "group": null},
+                     "value": {"name": "tasks.max", "value": "many",
+                               "errors": ["Invalid value many"]}}]}"#,
+            )
+            .create();
+        let put = server
+            .mock("PUT", "/connectors/users-sink/config")
+            .expect(0)
+            .create();
+
+        let update = ConfigUpdate {
+            validate: true,
+            dry_run: false,
+        };
+        let err = update
+            .edit(
+                &mock_client(&server),
+                "users-sink",
+                Some(Output::Name),
+                |config| {
+                    config.insert("tasks.max".to_string(), "many".to_string());
+                    Ok(())
+                },
+            )
+            .unwrap_err();
+
+        assert!(matches!(
+            err.downcast_ref::<KofrError>(),
+            Some(KofrError::Validation(_))
+        ));
+        validated.assert();
+        put.assert();
+    }
+
+    #[test]
+    fn test_comment_header_round_trip() {
+        let content = "{\n  \"tasks.max\": \"1\"\n}\n";
+        let header = edit_header("connector config has 1 error(s):\ntasks.max: Invalid value");
+
+        assert!(header
+            .lines()
+            .any(|line| line == "# tasks.max: Invalid value"));
+        assert_eq!(
+            strip_comment_header(&format!("{}{}", header, content)),
+            content
+        );
+        assert_eq!(strip_comment_header(content), content);
+        assert_eq!(strip_comment_header(&header), "");
+    }
+
+    /// an editor script changing tasks.max to an invalid value, running `then` when the file
+    /// is reopened with the rejection header. The path of the edited file is written to
+    /// `edited` in the same directory.
+    fn editor_script(dir: &std::path::Path, then: &str) -> Editor {
+        let path = dir.join("editor.sh");
+        std::fs::write(
+            &path,
+            format!(
+                "#!/bin/sh\necho \"$1\" > {}\nif grep -q '^#' \"$1\"; then {}; else sed -i 's/\"1\"/\"many\"/' \"$1\"; fi\n",
+                dir.join("edited").display(),
+                then
+            ),
+        )
+        .unwrap();
+        std::process::Command::new("chmod")
+            .arg("+x")
+            .arg(&path)
+            .status()
+            .unwrap();
+        Editor {
+            name: path.to_string_lossy().into_owned(),
+        }
+    }
+
+    /// content of the file last opened by an `editor_script`, removing it
+    fn take_edited(dir: &std::path::Path) -> String {
+        let edited = std::fs::read_to_string(dir.join("edited")).unwrap();
+        let edited = edited.trim();
+        let content = std::fs::read_to_string(edited).unwrap();
+        std::fs::remove_file(edited).unwrap();
+        content
+    }
+
+    /// a server rejecting every config with tasks.max set to "many"
+    fn rejecting_server() -> (mockito::ServerGuard, mockito::Mock) {
+        let mut server = Server::new();
+        server
+            .mock("GET", "/connectors/users-sink/config")
+            .with_body(r#"{"connector.class": "FileStreamSink", "tasks.max": "1"}"#)
+            .create();
+        server
+            .mock("PUT", "/connector-plugins/FileStreamSink/config/validate")
+            .with_body(
+                r#"{"name": "FileStreamSink", "error_count": 1, "groups": [], "configs": [
+                    {"definition": {"name": "tasks.max", "type": "INT", "required": true,
+                                    "default_value": null, "importance": "HIGH",
+                                    "documentation": null, "group": null},
+                     "value": {"name": "tasks.max", "value": "many",
+                               "errors": ["Invalid value many"]}}]}"#,
+            )
+            .create();
+        let put = server
+            .mock("PUT", "/connectors/users-sink/config")
+            .expect(0)
+            .create();
+        (server, put)
+    }
+
+    fn edit_command() -> Edit {
+        Edit {
+            name: "users-sink".to_string(),
+            format: ManifestFormat::Json,
+        }
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_saving_a_rejected_edit_unchanged_gives_up() {
+        let dir = tempfile::tempdir().unwrap();
+        let editor = editor_script(dir.path(), "true");
+        let (server, put) = rejecting_server();
+
+        let err = edit_command()
+            .edit_with(&editor, &mock_client(&server), Some(Output::Name))
+            .unwrap_err();
+
+        assert!(is_validation_error(&err));
+        let kept = take_edited(dir.path());
+        assert!(kept.contains("\"many\""));
+        assert!(!kept.starts_with('#'));
+        put.assert();
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_clearing_a_rejected_edit_keeps_a_copy() {
+        let dir = tempfile::tempdir().unwrap();
+        let editor = editor_script(dir.path(), ": > \"$1\"");
+        let (server, put) = rejecting_server();
+
+        edit_command()
+            .edit_with(&editor, &mock_client(&server), Some(Output::Name))
+            .unwrap();
+
+        assert!(take_edited(dir.path()).contains("\"many\""));
+        put.assert();
+    }
+
+    #[test]
+    fn test_waiting_for_deletion_outlasts_errors() {
+        let mut server = Server::new();
+        let failing = server
+            .mock("GET", "/connectors")
+            .with_status(500)
+            .with_body(r#"{"error_code": 500, "message": "worker restarting"}"#)
+            .expect(1)
+            .create();
+        let deleted = server
+            .mock("GET", "/connectors")
+            .with_body("[]")
+            .expect(1)
+            .create();
+        let wait = Wait {
+            name: "users-sink".to_string(),
+            state: WaitFor::Deleted,
+            timeout: Duration::from_secs(5),
+            interval: Duration::from_millis(10),
+        };
+
+        assert_eq!(wait.run(mock_client(&server), None).unwrap(), exitcode::OK);
+        failing.assert();
+        deleted.assert();
+    }
+
+    #[test]
+    fn test_diffing_reports_every_connector() {
+        let mut server = Server::new();
+        server
+            .mock("GET", "/connectors")
+            .with_body(r#"["users-sink", "orders-sink"]"#)
+            .create();
+        let failing = server
+            .mock("GET", "/connectors/users-sink/config")
+            .with_status(500)
+            .with_body(r#"{"error_code": 500, "message": "Request timed out"}"#)
+            .create();
+        let drifted = server
+            .mock("GET", "/connectors/orders-sink/config")
+            .with_body(
+                r#"{"name": "orders-sink", "connector.class": "FileStreamSink", "tasks.max": "2"}"#,
+            )
+            .create();
+        let mut manifest = tempfile::Builder::new().suffix(".yaml").tempfile().unwrap();
+        write!(
+            manifest,
+            "name: users-sink\nconfig:\n  connector.class: FileStreamSink\n---\nname: orders-sink\nconfig:\n  connector.class: FileStreamSink\n"
+        )
+        .unwrap();
+
+        let diff = Diff {
+            path: manifest.path().to_path_buf(),
+            unified: false,
+            concurrency: 1,
+        };
+        let err = diff
+            .run(mock_client(&server), Some(Output::Name))
+            .unwrap_err();
+
+        assert_eq!(err.to_string(), "failed diffing 1 out of 2 connectors");
+        failing.assert();
+        drifted.assert();
+    }
+
+    #[test]
+    fn test_applying_a_redacted_export_fails() {
+        let mut server = Server::new();
+        let changed = server.mock("PUT", Matcher::Any).expect(0).create();
+        let dir = tempfile::tempdir().unwrap();
+        let mut config: ConnectorConfig = serde_json::from_str(SINK_CONFIG).unwrap();
+        config.insert("connection.password".to_string(), "hunter2".to_string());
+        crate::manifest::redact_secrets(&mut config);
+        let exported = ExportedConnector {
+            name: ConnectorName("users-sink".to_string()),
+            config: config.into_iter().collect(),
+            status: None,
+        };
+        let file = dir.path().join("users-sink.json");
+        std::fs::write(&file, exported.render(ManifestFormat::Json).unwrap()).unwrap();
+
+        let apply = Apply {
+            path: file,
+            concurrency: 1,
+            allow_redacted: false,
+        };
+        let err = apply
+            .run(mock_client(&server), Some(Output::Name))
+            .unwrap_err();
+
+        assert!(is_validation_error(&err));
+        assert!(err
+            .to_string()
+            .contains("connector/users-sink: connection.password"));
+        changed.assert();
+    }
+
+    #[test]
+    fn test_waiting_for_running_requires_running_tasks() {
+        let status = r#"
+        {
+            "name": "orders-sink",
+            "connector": {"state": "RUNNING", "worker_id": "10.0.0.1:8083"},
+            "tasks": [
+                {"id": 0, "state": "RUNNING", "worker_id": "10.0.0.1:8083"},
+                {"id": 1, "state": "FAILED", "worker_id": "10.0.0.2:8083", "trace": "boom"}
+            ],
+            "type": "sink"
+        }"#;
+        let mut status: ConnectorStatus = serde_json::from_str(status).unwrap();
+
+        assert!(!WaitFor::Running.reached(&status));
+        status.tasks.pop();
+        assert!(WaitFor::Running.reached(&status));
+        status.tasks.clear();
+        assert!(!WaitFor::Running.reached(&status));
+    }
+
+    #[test]
+    fn test_sorting_connectors_breaks_ties_by_name() {
+        let connector = |name: &str, state: State, tasks: usize| VerboseConnector {
+            name: ConnectorName(name.to_string()),
+            state,
+            tasks,
+            connector_type: ConnectorType::Sink,
+            worker_id: "127.0.0.1:8083".to_string(),
+            connector_class: "-".to_string(),
+        };
+        let mut connectors = vec![
+            connector("c", State::Running, 1),
+            connector("b", State::Failed, 3),
+            connector("a", State::Running, 2),
+        ];
+
+        SortBy::State.sort(&mut connectors);
+        let names: Vec<&str> = connectors.iter().map(|c| c.name.0.as_str()).collect();
+        assert_eq!(names, vec!["b", "a", "c"]);
+
+        SortBy::Tasks.sort(&mut connectors);
+        let names: Vec<&str> = connectors.iter().map(|c| c.name.0.as_str()).collect();
+        assert_eq!(names, vec!["c", "a", "b"]);
+    }
+}
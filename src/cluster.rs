@@ -1,17 +1,56 @@
-#[derive(tabled::Tabled, Debug)]
+use serde::{Deserialize, Serialize};
+
+use crate::connect::HTTPClient;
+use crate::error::Result;
+
+impl HTTPClient {
+    /// version of the worker and id of its kafka cluster wrapping GET request to /
+    pub fn cluster_info(&self) -> Result<ClusterInfo> {
+        match self.send(
+            |uri| {
+                self.config
+                    .http_agent
+                    .get(uri)
+                    .set("Accept", "application/json")
+            },
+            None,
+        ) {
+            Ok(response) => Ok(response.into_json()?),
+            Err(err) => Err(err.into()),
+        }
+    }
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct ClusterInfo {
+    pub version: String,
+    pub commit: String,
+    pub kafka_cluster_id: String,
+}
+
+#[derive(tabled::Tabled, Debug, Serialize)]
 pub struct UriStatus {
     #[tabled(rename = "HOST")]
     pub uri: String,
     #[tabled(rename = "STATE")]
     pub state: UriState,
+    /// why the host could not be reached, shown by the wide output
+    #[tabled(rename = "REASON", display_with = "display_option")]
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub reason: Option<String>,
     #[tabled(skip)]
+    #[serde(skip)]
     pub id: String,
 }
 
-#[derive(Debug)]
+#[derive(Debug, Serialize)]
 pub enum UriState {
     Online,
     Offline,
+    /// the host is up but rejected the configured credentials
+    Unauthorized,
+    /// the host is up but the TLS handshake failed, e.g. an untrusted certificate
+    TlsHandshakeFailed,
 }
 
 impl std::fmt::Display for UriState {
@@ -19,25 +58,98 @@ impl std::fmt::Display for UriState {
         match self {
             Self::Online => write!(f, "Online"),
             Self::Offline => write!(f, "Offline"),
+            Self::Unauthorized => write!(f, "Unauthorized"),
+            Self::TlsHandshakeFailed => write!(f, "TLSHandshakeFailed"),
         }
     }
 }
 
-pub fn get_uri_status(host: &str) -> UriStatus {
-    match ureq::get(host).set("Accept", "application/json").call() {
-        Ok(response) => {
-            let response: serde_json::Value = response.into_json().unwrap();
-            let id = response.get("kafka_cluster_id").unwrap().to_string();
-            UriStatus {
+/// Probes a host of a cluster, a host answering with anything but a Connect worker's info is
+/// reported offline
+pub fn get_uri_status(agent: &ureq::Agent, host: &str) -> UriStatus {
+    match agent.get(host).set("Accept", "application/json").call() {
+        Ok(response) => match cluster_id(response) {
+            Ok(id) => UriStatus {
                 uri: host.to_string(),
                 state: UriState::Online,
+                reason: None,
                 id,
-            }
-        }
-        Err(_) => UriStatus {
+            },
+            Err(reason) => UriStatus {
+                uri: host.to_string(),
+                state: UriState::Offline,
+                reason: Some(reason),
+                id: "".to_string(),
+            },
+        },
+        Err(ureq::Error::Status(code @ (401 | 403), _)) => UriStatus {
+            uri: host.to_string(),
+            state: UriState::Unauthorized,
+            reason: Some(format!("status code {code}")),
+            id: "".to_string(),
+        },
+        Err(ureq::Error::Transport(transport)) => UriStatus {
+            uri: host.to_string(),
+            state: if crate::tls::is_handshake_error(&transport) {
+                UriState::TlsHandshakeFailed
+            } else {
+                UriState::Offline
+            },
+            reason: Some(transport.to_string()),
+            id: "".to_string(),
+        },
+        Err(err) => UriStatus {
             uri: host.to_string(),
             state: UriState::Offline,
+            reason: Some(err.to_string()),
             id: "".to_string(),
         },
     }
 }
+
+fn cluster_id(response: ureq::Response) -> std::result::Result<String, String> {
+    let response: serde_json::Value = response
+        .into_json()
+        .map_err(|err| format!("not a Connect worker: {err}"))?;
+    response
+        .get("kafka_cluster_id")
+        .map(|id| id.to_string())
+        .ok_or_else(|| "not a Connect worker: no kafka_cluster_id in the response".to_string())
+}
+
+fn display_option(reason: &Option<String>) -> String {
+    reason.as_deref().unwrap_or("-").to_string()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_hosts_answering_something_else_are_offline() {
+        let mut server = mockito::Server::new();
+        server
+            .mock("GET", "/login")
+            .with_header("content-type", "text/html")
+            .with_body("<html>please log in</html>")
+            .create();
+        server
+            .mock("GET", "/other")
+            .with_body(r#"{"status": "ok"}"#)
+            .create();
+        server
+            .mock("GET", "/")
+            .with_body(r#"{"version": "3.6.0", "commit": "abc", "kafka_cluster_id": "xyz"}"#)
+            .create();
+        let agent = ureq::agent();
+
+        let login = get_uri_status(&agent, &format!("{}/login", server.url()));
+        assert!(matches!(login.state, UriState::Offline));
+        assert!(login.reason.unwrap().starts_with("not a Connect worker"));
+        let other = get_uri_status(&agent, &format!("{}/other", server.url()));
+        assert!(matches!(other.state, UriState::Offline));
+        let worker = get_uri_status(&agent, &server.url());
+        assert!(matches!(worker.state, UriState::Online));
+        assert!(worker.id.contains("xyz"));
+    }
+}
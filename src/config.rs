@@ -1,8 +1,13 @@
+use std::collections::BTreeMap;
 use std::path::PathBuf;
+use std::time::Duration;
 
-use anyhow::{anyhow, Context, Ok, Result};
+use base64::{engine::general_purpose::STANDARD, Engine};
 use home::home_dir;
 use serde::{Deserialize, Serialize};
+use ureq::Agent;
+
+use crate::error::{KofrError, Result};
 
 #[derive(Serialize, Deserialize, PartialEq, Debug)]
 pub struct Config {
@@ -15,7 +20,8 @@ pub struct Config {
 
 impl Config {
     pub fn new() -> Result<Self> {
-        let mut default_config_path = home_dir().context("could not get user's home dir")?;
+        let mut default_config_path = home_dir()
+            .ok_or_else(|| KofrError::Config("could not get user's home dir".to_string()))?;
         default_config_path.push(".kofr/config");
         let config = Self {
             current_cluster: None,
@@ -23,21 +29,27 @@ impl Config {
             file_path: PathBuf::new(),
         };
         if !default_config_path.exists() {
-            std::fs::create_dir_all(default_config_path.parent().unwrap())?;
-            let config_yaml =
-                serde_yaml::to_string(&config).context("invalid config yaml format")?;
-            std::fs::write(&default_config_path, config_yaml)
-                .context("failed writing file to filesystem")?;
+            std::fs::create_dir_all(default_config_path.parent().unwrap()).map_err(|err| {
+                KofrError::Config(format!("failed creating the config directory: {err}"))
+            })?;
+            let config_yaml = serde_yaml::to_string(&config)
+                .map_err(|err| KofrError::Config(format!("invalid config yaml format: {err}")))?;
+            std::fs::write(&default_config_path, config_yaml).map_err(|err| {
+                KofrError::Config(format!("failed writing file to filesystem: {err}"))
+            })?;
         }
         Ok(config)
     }
 
     pub fn with_file(mut self, path: PathBuf) -> Result<Self> {
-        let config = std::fs::read_to_string(&path).with_context(|| {
-            format!("error reading config file \"{}\"", &path.to_string_lossy())
+        let config = std::fs::read_to_string(&path).map_err(|err| {
+            KofrError::Config(format!(
+                "error reading config file \"{}\": {err}",
+                &path.to_string_lossy()
+            ))
         })?;
-        let deserialized_config: Self =
-            serde_yaml::from_str(&config).context("invalid config file format")?;
+        let deserialized_config: Self = serde_yaml::from_str(&config)
+            .map_err(|err| KofrError::Config(format!("invalid config file format: {err}")))?;
 
         self.current_cluster = deserialized_config.current_cluster;
         self.clusters = deserialized_config.clusters;
@@ -46,36 +58,329 @@ impl Config {
         Ok(self)
     }
 
+    pub fn cluster(&self, name: &str) -> Result<&ClusterContext> {
+        self.clusters
+            .iter()
+            .find(|&c| c.name == name)
+            .ok_or_else(|| {
+                KofrError::Config(format!("Cluster with name \"{}\" could not be found", name))
+            })
+    }
+
     pub fn current_context(&self) -> Result<&ClusterContext> {
-        let cluster_name = self.current_cluster.as_deref().ok_or(anyhow::anyhow!(
+        let cluster_name = self.current_cluster.as_deref().ok_or(KofrError::Config(
             "No current context was set\n consider using command: kofr config use-cluster <CLUSTER>"
+                .to_string(),
         ))?;
         self.clusters
             .iter()
             .find(|&c| c.name == cluster_name)
-            .ok_or(anyhow::anyhow!(format!(
+            .ok_or_else(|| KofrError::Config(format!(
                 "Cluster with name: \"{}\" could not be found\nConsider setting one with command: kofr config use-cluster <CLUSTER>",
                 &cluster_name
             )))
     }
 }
 
-#[derive(Serialize, Deserialize, PartialEq, Debug)]
+#[derive(Serialize, Deserialize, PartialEq, Debug, Default)]
+#[serde(rename_all = "kebab-case")]
 pub struct ClusterContext {
     pub name: String,
     pub hosts: Vec<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub auth: Option<Auth>,
+    /// PEM file of the CA certificates trusted for https hosts, replacing the default roots
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub ca_file: Option<String>,
+    /// PEM client certificate for mutual TLS, requires `client-key`
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub client_cert: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub client_key: Option<String>,
+    /// accept any server certificate, only meant for testing
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    pub insecure_skip_verify: bool,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub retry: Option<RetryPolicy>,
+}
+
+/// How failed requests are retried, on the other hosts of a cluster when a host is unreachable
+#[derive(Serialize, Deserialize, PartialEq, Debug, Clone)]
+#[serde(rename_all = "kebab-case", default, deny_unknown_fields)]
+pub struct RetryPolicy {
+    /// retries of a single request, each on the next host of the cluster
+    pub retries: u32,
+    /// wait before the first retry, doubled on every following retry
+    #[serde(with = "humantime_serde")]
+    pub backoff: Duration,
+    #[serde(with = "humantime_serde")]
+    pub max_backoff: Duration,
+}
+
+impl Default for RetryPolicy {
+    fn default() -> Self {
+        Self {
+            retries: 6,
+            backoff: Duration::from_millis(500),
+            max_backoff: Duration::from_secs(10),
+        }
+    }
+}
+
+impl RetryPolicy {
+    /// wait before the given retry, starting at 1
+    pub fn backoff(&self, retry: u32) -> Duration {
+        self.backoff
+            .saturating_mul(2u32.saturating_pow(retry.saturating_sub(1)))
+            .min(self.max_backoff)
+    }
+}
+
+/// Credentials sent with every request to a cluster. A value may be a single environment
+/// variable reference, e.g. `password: ${CONNECT_PASSWORD}`, any other value is used as is
+#[derive(Serialize, Deserialize, PartialEq, Debug)]
+#[serde(untagged, deny_unknown_fields)]
+pub enum Auth {
+    Basic {
+        username: String,
+        password: String,
+    },
+    Bearer {
+        token: String,
+    },
+    /// a command printing a bearer token to stdout, run once per kofr invocation by `sh -c`, or
+    /// by `cmd /C` on windows
+    TokenCommand {
+        #[serde(rename = "token-command")]
+        token_command: String,
+    },
+}
+
+impl Auth {
+    /// value of the `Authorization` header
+    pub fn header(&self) -> Result<String> {
+        match self {
+            Self::Basic { username, password } => {
+                let credentials = format!("{}:{}", expand(username)?, expand(password)?);
+                Ok(format!("Basic {}", STANDARD.encode(credentials)))
+            }
+            Self::Bearer { token } => Ok(format!("Bearer {}", expand(token)?)),
+            Self::TokenCommand { token_command } => {
+                let output = shell_command(token_command).output().map_err(|err| {
+                    KofrError::Config(format!(
+                        "failed running token command: \"{token_command}\": {err}"
+                    ))
+                })?;
+                if !output.status.success() {
+                    return Err(KofrError::Config(format!(
+                        "token command: \"{}\" failed: {}",
+                        token_command,
+                        String::from_utf8_lossy(&output.stderr).trim()
+                    )));
+                }
+                let token = String::from_utf8(output.stdout).map_err(|_| {
+                    KofrError::Config("token command printed an invalid utf-8 token".to_string())
+                })?;
+                let token = token.trim();
+                if token.is_empty() {
+                    return Err(KofrError::Config(format!(
+                        "token command: \"{}\" printed an empty token",
+                        token_command
+                    )));
+                }
+                Ok(format!("Bearer {token}"))
+            }
+        }
+    }
+}
+
+/// resolves a value that is entirely a `$VAR` or `${VAR}` reference, other values are kept as
+/// they are so that credentials may hold a `$`
+fn expand(value: &str) -> Result<String> {
+    let Some(name) = env_reference(value) else {
+        return Ok(value.to_string());
+    };
+    std::env::var(name)
+        .map_err(|err| KofrError::Config(format!("could not expand \"{value}\": {err}")))
+}
+
+fn env_reference(value: &str) -> Option<&str> {
+    let name = match value.strip_prefix("${") {
+        Some(rest) => rest.strip_suffix('}')?,
+        None => value.strip_prefix('$')?,
+    };
+    let valid = name.starts_with(|c: char| c.is_ascii_alphabetic() || c == '_')
+        && name.chars().all(|c| c.is_ascii_alphanumeric() || c == '_');
+    valid.then_some(name)
+}
+
+#[cfg(not(windows))]
+fn shell_command(command: &str) -> std::process::Command {
+    let mut shell = std::process::Command::new("sh");
+    shell.arg("-c").arg(command);
+    shell
+}
+
+#[cfg(windows)]
+fn shell_command(command: &str) -> std::process::Command {
+    let mut shell = std::process::Command::new("cmd");
+    shell.arg("/C").arg(command);
+    shell
 }
 
 impl ClusterContext {
-    pub fn available_host(&self) -> Result<String> {
+    /// returns the host that answered last time without probing it, or probes the hosts
+    /// for the first one that answers and remembers it for the next runs in
+    /// `~/.kofr/healthy-hosts`
+    pub fn healthy_host(&self, agent: &Agent) -> Result<String> {
+        if let Some(host) = last_healthy_host(&self.name) {
+            if let Ok(host) = self.host(&host) {
+                return Ok(host);
+            }
+        }
+        let host = self.available_host(agent)?;
+        remember_healthy_host(&self.name, &host);
+        Ok(host)
+    }
+
+    /// returns the first host that answers, hosts rejecting the configured credentials fail
+    /// right away instead of being skipped as unavailable
+    pub fn available_host(&self, agent: &Agent) -> Result<String> {
+        let mut last_error = None;
         for host in &self.hosts {
-            if ureq::get(host).call().is_ok() {
-                return Ok(host.to_string());
+            match agent.get(host).call() {
+                Ok(_) => return Ok(host.to_string()),
+                Err(ureq::Error::Status(code @ (401 | 403), _)) => {
+                    return Err(KofrError::Auth(format!(
+                        "host \"{}\" of cluster: \"{}\" rejected the credentials ({})",
+                        host, self.name, code
+                    )))
+                }
+                Err(err) => last_error = Some(err),
             }
         }
-        Err(anyhow!(
+        let error = format!(
             "client has run out of available hosts to talk to for cluster: \"{}\"",
             self.name
-        ))
+        );
+        match last_error {
+            Some(last_error) => Err(KofrError::Transport(format!(
+                "{}, last error: {}",
+                error, last_error
+            ))),
+            None => Err(KofrError::Transport(error)),
+        }
+    }
+
+    /// returns the given host if it belongs to this cluster, without probing it
+    pub fn host(&self, host: &str) -> Result<String> {
+        self.hosts
+            .iter()
+            .find(|&h| h.trim_end_matches('/') == host.trim_end_matches('/'))
+            .map(|h| h.to_string())
+            .ok_or_else(|| {
+                KofrError::Config(format!(
+                    "host \"{}\" is not one of the hosts of cluster: \"{}\"",
+                    host, self.name
+                ))
+            })
+    }
+}
+
+/// file keeping the last host of each cluster that answered, see [`ClusterContext::healthy_host`]
+fn healthy_hosts_path() -> Option<PathBuf> {
+    let mut path = home_dir()?;
+    path.push(".kofr/healthy-hosts");
+    Some(path)
+}
+
+fn last_healthy_host(cluster: &str) -> Option<String> {
+    let hosts = std::fs::read_to_string(healthy_hosts_path()?).ok()?;
+    let mut hosts: BTreeMap<String, String> = serde_yaml::from_str(&hosts).ok()?;
+    hosts.remove(cluster)
+}
+
+/// remembers the host that answered for the next runs, failing to do so is not an error as
+/// the next run probes the hosts again
+pub(crate) fn remember_healthy_host(cluster: &str, host: &str) {
+    let Some(path) = healthy_hosts_path() else {
+        return;
+    };
+    let mut hosts: BTreeMap<String, String> = std::fs::read_to_string(&path)
+        .ok()
+        .and_then(|hosts| serde_yaml::from_str(&hosts).ok())
+        .unwrap_or_default();
+    if hosts.get(cluster).map(String::as_str) == Some(host) {
+        return;
+    }
+    hosts.insert(cluster.to_string(), host.to_string());
+    if let Ok(hosts) = serde_yaml::to_string(&hosts) {
+        let _ = std::fs::write(path, hosts);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parsing_cluster_auth() {
+        let clusters = r#"
+        - name: basic
+          hosts: [http://localhost:8083]
+          auth:
+            username: admin
+            password: s3cret
+        - name: bearer
+          hosts: [http://localhost:8083]
+          auth:
+            token: abc
+        - name: command
+          hosts: [http://localhost:8083]
+          auth:
+            token-command: echo abc
+        - name: none
+          hosts: [http://localhost:8083]
+        "#;
+        let clusters: Vec<ClusterContext> = serde_yaml::from_str(clusters).unwrap();
+
+        let headers: Vec<Option<String>> = clusters
+            .iter()
+            .map(|c| c.auth.as_ref().map(|a| a.header().unwrap()))
+            .collect();
+        assert_eq!(
+            headers,
+            vec![
+                Some("Basic YWRtaW46czNjcmV0".to_string()),
+                Some("Bearer abc".to_string()),
+                Some("Bearer abc".to_string()),
+                None,
+            ]
+        );
+    }
+
+    #[test]
+    fn test_only_whole_env_references_are_expanded() {
+        std::env::set_var("KOFR_TEST_PASSWORD", "s3cret");
+
+        assert_eq!(expand("${KOFR_TEST_PASSWORD}").unwrap(), "s3cret");
+        assert_eq!(expand("$KOFR_TEST_PASSWORD").unwrap(), "s3cret");
+        assert_eq!(expand("pa$$word").unwrap(), "pa$$word");
+        assert_eq!(
+            expand("a$KOFR_TEST_PASSWORD").unwrap(),
+            "a$KOFR_TEST_PASSWORD"
+        );
+        assert_eq!(expand("${not a var}").unwrap(), "${not a var}");
+        let err = expand("${KOFR_TEST_UNSET_PASSWORD}").unwrap_err();
+        assert!(matches!(err, KofrError::Config(_)));
+    }
+
+    #[test]
+    fn test_retry_backoff_doubles_up_to_max() {
+        let retry: RetryPolicy = serde_yaml::from_str("backoff: 100ms\nmax-backoff: 1s").unwrap();
+        assert_eq!(retry.retries, 6);
+
+        let backoffs: Vec<u128> = (1..=5).map(|r| retry.backoff(r).as_millis()).collect();
+        assert_eq!(backoffs, vec![100, 200, 400, 800, 1000]);
     }
 }
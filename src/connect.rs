@@ -1,164 +1,185 @@
 use std::fmt::Display;
-use std::{collections::HashMap, str::FromStr};
-
-use anyhow::{anyhow, Context, Result};
+use std::sync::RwLock;
+use std::time::Duration;
+use std::{
+    collections::{BTreeMap, HashMap},
+    str::FromStr,
+};
+
+use anyhow::anyhow;
 use serde::{Deserialize, Serialize};
 use ureq::{Agent, Error};
 
-use crate::tasks::{Task, TaskStatus};
+use crate::config::{ClusterContext, RetryPolicy};
+use crate::error::{KofrError, Result};
+use crate::history::{Change, History};
+use crate::loggers::{LoggerLevel, Loggers, Scope};
+use crate::offsets::{ConnectorOffsets, OffsetsResponse};
+use crate::tasks::{Task, TaskState, TaskStatus};
 
+/// A blocking client of a Connect cluster's REST API, see the crate docs for an example
 pub struct HTTPClient {
     pub config: HTTPClientConfig,
+    current_uri: RwLock<String>,
+    failover: Option<Failover>,
+    retry: Option<RetryPolicy>,
+    retry_callback: Option<RetryCallback>,
+    host_cache: bool,
+    history: Option<History>,
+}
+
+type RetryCallback = Box<dyn Fn(&Retrying) + Send + Sync>;
+
+/// A request about to be retried, passed to the callback set with
+/// [`HTTPClient::with_retry_callback`]. Displays as a one line message.
+#[derive(Debug)]
+pub enum Retrying<'a> {
+    /// the cluster refused the request while its workers rebalance
+    Rebalancing {
+        backoff: Duration,
+        retry: u32,
+        retries: u32,
+    },
+    /// the host could not be reached, the request is sent to the next host
+    Failover {
+        host: &'a str,
+        next: &'a str,
+        error: String,
+    },
 }
 
 impl HTTPClient {
+    /// builds a client talking to a single host, failed requests are not retried unless a policy
+    /// is set with [`HTTPClient::with_retry`]
     pub fn from_config(config: HTTPClientConfig) -> Self {
-        Self { config }
+        Self {
+            current_uri: RwLock::new(config.connect_uri.clone()),
+            config,
+            failover: None,
+            retry: None,
+            retry_callback: None,
+            host_cache: false,
+            history: None,
+        }
     }
 
-    pub fn list_connectors_status(&self) -> Result<Vec<VerboseConnector>> {
-        let uri = &self.config.connect_uri;
-        let _endpoint = self.valid_uri(uri);
-
-        let response = match self
-            .config
-            .http_agent
-            .get(&_endpoint)
-            .set("Accept", "application/json")
-            .query("expand", "status")
-            .call()
-        {
-            Ok(response) => response,
-            Err(ureq::Error::Status(_, r)) => return Err(anyhow!("{}", r.into_string()?)),
-            Err(err) => return Err(anyhow!("{}", err)),
-        };
+    /// builds a client talking to the first host of the given cluster that answers, failing
+    /// over to its other hosts
+    pub fn for_cluster(cluster: &ClusterContext) -> Result<Self> {
+        let http_agent = cluster_agent(cluster)?;
+        Ok(Self::from_config(HTTPClientConfig {
+            connect_uri: cluster.available_host(&http_agent)?,
+            http_agent,
+        })
+        .with_retry(Some(cluster.retry.clone().unwrap_or_default()))
+        .with_failover(cluster))
+    }
 
-        let response_body = response.into_string()?;
+    /// sets the policy retrying failed requests, `None` disables retries
+    pub fn with_retry(mut self, retry: Option<RetryPolicy>) -> Self {
+        self.retry = retry;
+        self
+    }
 
-        let mut _vec: Vec<VerboseConnector> = Vec::new();
-        if response_body == "[]" {
-            return Ok(_vec);
-        }
+    /// calls back before every retry, e.g. to tell the user why a command is waiting
+    pub fn with_retry_callback(
+        mut self,
+        callback: impl Fn(&Retrying) + Send + Sync + 'static,
+    ) -> Self {
+        self.retry_callback = Some(Box::new(callback));
+        self
+    }
+
+    /// retries requests failing to reach the current host on the other hosts of the cluster
+    pub fn with_failover(mut self, cluster: &ClusterContext) -> Self {
+        self.failover = Some(Failover::new(cluster));
+        self
+    }
 
-        let response: serde_json::Map<String, serde_json::Value> =
-            serde_json::from_str(&response_body)?;
-        let connectors_status: serde_json::Map<String, serde_json::Value> = response;
+    /// remembers the host requests failed over to for the next runs, see
+    /// [`ClusterContext::healthy_host`]
+    pub fn with_host_cache(mut self) -> Self {
+        self.host_cache = true;
+        self
+    }
 
-        for entry in &connectors_status {
-            let status = entry.1.get("status").with_context(|| {
-                format!(
-                    "no field named status in response json struct: {}",
-                    &response_body
-                )
-            })?;
-            let tasks = status
-                .get("tasks")
-                .with_context(|| {
-                    format!(
-                        "no field named tasks in response json struct: {}",
-                        &response_body
-                    )
-                })?
-                .as_array()
-                .with_context(|| {
-                    format!(
-                        r#"expected array in "tasks" key, found something: {}"#,
-                        &response_body
-                    )
-                })?
-                .len();
-            let state = State::from_str(
-                status
-                    .get("connector")
-                    .with_context(|| {
-                        format!(
-                            r#"no field named "connector" in response json struct: {}"#,
-                            &response_body
-                        )
-                    })?
-                    .get("state")
-                    .with_context(|| {
-                        format!(
-                            r#"no field named "state" in response json struct: {}"#,
-                            &response_body
-                        )
-                    })?
-                    .as_str()
-                    .with_context(|| {
-                        format!(
-                            r#"expected string value of key "state" found something else: {}"#,
-                            &response_body
-                        )
-                    })?,
-            )?;
-            let worker_id = status
-                .get("connector")
-                .with_context(|| {
-                    format!(
-                        r#"no field named "connector" in response json struct: {}"#,
-                        &response_body
-                    )
-                })?
-                .get("worker_id")
-                .with_context(|| {
-                    format!(
-                        r#"no field named "worker_id" in response json struct: {}"#,
-                        &response_body
-                    )
-                })?
-                .as_str()
-                .with_context(|| {
-                    format!(
-                        r#"expected string value of key "worker_id" found something else: {}"#,
-                        &response_body
-                    )
-                })?;
-
-            let connector_type = status
-                .get("type")
-                .with_context(|| {
-                    format!(
-                        r#"no field named "type" in response json struct: {}"#,
-                        &response_body
-                    )
-                })?
-                .as_str()
-                .unwrap();
-            let connector_type = ConnectorType::from_str(connector_type)?;
-
-            _vec.push(VerboseConnector {
-                name: ConnectorName(String::from(entry.0)),
-                tasks,
-                state,
-                connector_type,
-                worker_id: worker_id.to_string(),
-            })
-        }
-
-        Ok(_vec)
+    /// snapshots the config of a connector into the history before updating or deleting it,
+    /// `None` disables snapshots
+    pub fn with_history(mut self, history: Option<History>) -> Self {
+        self.history = history;
+        self
+    }
+
+    /// lists connectors with their status and class wrapping GET request to
+    /// /connectors?expand=status&expand=info
+    pub fn list_connectors_status(&self) -> Result<Vec<VerboseConnector>> {
+        let response = self.send(
+            |uri| {
+                self.config
+                    .http_agent
+                    .get(&self.valid_uri(uri))
+                    .set("Accept", "application/json")
+                    .query("expand", "status")
+                    .query("expand", "info")
+            },
+            None,
+        )?;
+
+        verbose_connectors(&response.into_string()?)
     }
 
+    /// lists the names of active connectors wrapping GET request to /connectors
+    pub fn list_connectors(&self) -> Result<Vec<String>> {
+        match self.send(
+            |uri| {
+                self.config
+                    .http_agent
+                    .get(&self.valid_uri(uri))
+                    .set("Accept", "application/json")
+            },
+            None,
+        ) {
+            Ok(response) => Ok(response.into_json()?),
+            Err(err) => Err(err.into()),
+        }
+    }
+
+    /// creates a connector wrapping POST request to /connectors
     pub fn create_connector(&self, c: &CreateConnector) -> Result<Connector> {
-        let uri = &self.config.connect_uri;
-        let _endpoint = self.valid_uri(uri);
-
-        match self
-            .config
-            .http_agent
-            .post(&_endpoint)
-            .set("Content-Type", "application/json")
-            .set("Accept", "application/json")
-            .send_json(c)
-        {
-            Ok(response) => response
-                .into_json::<Connector>()
-                .context("could not parse response returned"),
-            Err(Error::Status(_, response)) => Err(anyhow!(" {:?}", response.into_string()?)),
-            Err(err) => Err(anyhow!("{}", err)),
+        match self.send(
+            |uri| {
+                self.config
+                    .http_agent
+                    .post(&self.valid_uri(uri))
+                    .set("Content-Type", "application/json")
+                    .set("Accept", "application/json")
+            },
+            Some(&serde_json::to_value(c)?),
+        ) {
+            Ok(response) => Ok(response.into_json::<Connector>()?),
+            Err(err) => Err(err.into()),
         }
     }
 
-    pub fn desribe_connector(&self, name: &str) -> Result<DescribeConnector> {
+    /// config, tasks and type of a connector wrapping GET request to /connectors/<name>
+    pub fn get_connector(&self, name: &str) -> Result<Connector> {
+        match self.send(
+            |uri| {
+                self.config
+                    .http_agent
+                    .get(&format!("{}/{}", self.valid_uri(uri), name))
+                    .set("Accept", "application/json")
+            },
+            None,
+        ) {
+            Ok(response) => Ok(response.into_json()?),
+            Err(ureq::Error::Status(404, _)) => Err(KofrError::not_found("connector", name)),
+            Err(err) => Err(err.into()),
+        }
+    }
+
+    /// config and status of a connector
+    pub fn describe_connector(&self, name: &str) -> Result<DescribeConnector> {
         let status: ConnectorStatus = self.get_connector_status(name)?;
         let config: ConnectorConfig = self.get_connector_config(name)?;
 
@@ -171,166 +192,532 @@ impl HTTPClient {
         })
     }
 
-    // updates a connector's config wrapping PUT request to /connectors/<name>/config
-    pub fn put_connector(self, name: &str, config: ConnectorConfig) -> Result<Connector> {
-        let uri = &self.config.connect_uri;
-        let config_endpoint = format!("{}/{}/config", self.valid_uri(uri), name);
-        match self
-            .config
-            .http_agent
-            .put(&config_endpoint)
-            .set("Accept", "application/json")
-            .set("Content-Type", "application/json")
-            .send_json(config)
-        {
-            Ok(response) => match response.into_json::<Connector>() {
-                Ok(response) => Ok(response),
-                Err(e) => Err(anyhow!("{e}")),
+    /// updates a connector's config wrapping PUT request to /connectors/<name>/config
+    pub fn put_connector(&self, name: &str, config: ConnectorConfig) -> Result<Connector> {
+        self.snapshot(name, Change::Update);
+        match self.send(
+            |uri| {
+                self.config
+                    .http_agent
+                    .put(&format!("{}/{}/config", self.valid_uri(uri), name))
+                    .set("Accept", "application/json")
+                    .set("Content-Type", "application/json")
             },
-            Err(ureq::Error::Status(404, _)) => {
-                Err(anyhow!("connector: \"{}\" was not found", name))
-            }
-            Err(ureq::Error::Status(_, response)) => {
-                let response = response
-                    .into_string()
-                    .context("resposne was larger than 10MBs")?;
-                Err(anyhow!("{response}"))
-            }
-            Err(ureq::Error::Transport(transport)) => {
-                let message = transport.message().unwrap_or_default();
-                Err(anyhow!("unexpected transport error:\n{message}"))
-            }
+            Some(&serde_json::to_value(config)?),
+        ) {
+            Ok(response) => Ok(response.into_json::<Connector>()?),
+            Err(ureq::Error::Status(404, _)) => Err(KofrError::not_found("connector", name)),
+            Err(err) => Err(err.into()),
         }
     }
 
+    /// wraps GET request to /connectors/<name>/config
     pub fn get_connector_config(&self, name: &str) -> Result<ConnectorConfig> {
-        let uri = &self.config.connect_uri;
-        let config_endpoint = format!("{}/{}/config", self.valid_uri(uri), name);
-        match self
-            .config
-            .http_agent
-            .get(&config_endpoint)
-            .set("Accept", "application/json")
-            .call()
-        {
-            Ok(response) => response
-                .into_json()
-                .context("failed parsing connector config's json"),
+        match self.send(
+            |uri| {
+                self.config
+                    .http_agent
+                    .get(&format!("{}/{}/config", self.valid_uri(uri), name))
+                    .set("Accept", "application/json")
+            },
+            None,
+        ) {
+            Ok(response) => Ok(response.into_json()?),
 
-            Err(ureq::Error::Status(404, _)) => {
-                Err(anyhow!("connector: \"{}\" was not found", name))
-            }
-            Err(ureq::Error::Status(_, r)) => Err(anyhow!("{}", r.into_string()?)),
-            Err(err) => Err(anyhow!("{}", err)),
+            Err(ureq::Error::Status(404, _)) => Err(KofrError::not_found("connector", name)),
+            Err(err) => Err(err.into()),
         }
     }
 
+    /// wraps GET request to /connectors/<name>/status
     pub fn get_connector_status(&self, name: &str) -> Result<ConnectorStatus> {
-        let uri = &self.config.connect_uri;
-        let status_endpoint = format!("{}/{}/status", self.valid_uri(uri), name);
-        match self
-            .config
-            .http_agent
-            .get(&status_endpoint)
-            .set("Accept", "application/json")
-            .call()
-        {
-            Ok(response) => response
-                .into_json()
-                .context("failed parsing connector status's json"),
-            Err(ureq::Error::Status(404, _)) => {
-                Err(anyhow!("connector: \"{}\" was not found", name))
-            }
-            Err(ureq::Error::Status(_, r)) => Err(anyhow!("{}", r.into_string()?)),
-            Err(err) => Err(anyhow!("{}", err)),
+        match self.send(
+            |uri| {
+                self.config
+                    .http_agent
+                    .get(&format!("{}/{}/status", self.valid_uri(uri), name))
+                    .set("Accept", "application/json")
+            },
+            None,
+        ) {
+            Ok(response) => Ok(response.into_json()?),
+            Err(ureq::Error::Status(404, _)) => Err(KofrError::not_found("connector", name)),
+            Err(err) => Err(err.into()),
         }
     }
 
+    /// restarts a connector wrapping POST request to /connectors/<name>/restart, returns the
+    /// status of the restarting connector and tasks when tasks are included
     pub fn restart_connector(
-        self,
+        &self,
         name: &str,
         include_tasks: bool,
         only_failed: bool,
-    ) -> Result<()> {
-        let uri = &self.config.connect_uri;
-        let restart_endpoint = format!("{}/{}/restart", self.valid_uri(uri), name);
-        match self
-            .config
-            .http_agent
-            .post(&restart_endpoint)
-            .set("Accept", "application/json")
-            .query("includeTasks", &include_tasks.to_string())
-            .query("onlyFailed", &only_failed.to_string())
-            .call()
-        {
+    ) -> Result<Option<ConnectorStatus>> {
+        match self.send(
+            |uri| {
+                self.config
+                    .http_agent
+                    .post(&format!("{}/{}/restart", self.valid_uri(uri), name))
+                    .set("Accept", "application/json")
+                    .query("includeTasks", &include_tasks.to_string())
+                    .query("onlyFailed", &only_failed.to_string())
+            },
+            None,
+        ) {
             Ok(response) => match response.status() {
-                200 | 204 => Ok(()),
-                202 => {
-                    let response: ConnectorStatus = response.into_json()?;
-                    let response = serde_json::to_string_pretty(&response)?;
-                    println!("{}", response);
-                    Ok(())
-                }
-                _ => {
-                    let response = response
-                        .into_string()
-                        .expect("response was larger than 10MBs");
-                    println!("{response}");
-                    Ok(())
-                }
+                202 => Ok(Some(response.into_json()?)),
+                _ => Ok(None),
             },
-            Err(ureq::Error::Status(_, response)) => {
-                let response = response
-                    .into_string()
-                    .context("response was larger than 10MBs")?;
-                Err(anyhow!("{response}"))
-            }
-            Err(err) => Err(anyhow!("{err}")),
+            Err(ureq::Error::Status(404, _)) => Err(KofrError::not_found("connector", name)),
+            Err(err) => Err(err.into()),
         }
     }
 
-    pub fn pause_connector(self, name: &str) -> Result<()> {
-        let uri = &self.config.connect_uri;
-        let pause_endpoint = format!("{}/{}/pause", self.valid_uri(uri), name);
-        match self.config.http_agent.put(&pause_endpoint).call() {
+    /// pauses a connector and its tasks wrapping PUT request to /connectors/<name>/pause
+    pub fn pause_connector(&self, name: &str) -> Result<()> {
+        match self.send(
+            |uri| {
+                self.config
+                    .http_agent
+                    .put(&format!("{}/{}/pause", self.valid_uri(uri), name))
+            },
+            None,
+        ) {
             Ok(_) => Ok(()),
-            Err(ureq::Error::Status(_, r)) => Err(anyhow!("{}", r.into_string()?)),
-            Err(err) => Err(anyhow!("{}", err)),
+            Err(ureq::Error::Status(404, _)) => Err(KofrError::not_found("connector", name)),
+            Err(err) => Err(err.into()),
         }
     }
 
-    pub fn resume_connector(self, name: &str) -> Result<()> {
-        let uri = &self.config.connect_uri;
-        let resume_endpoint = format!("{}/{}/resume", self.valid_uri(uri), name);
-        match self.config.http_agent.put(&resume_endpoint).call() {
+    /// stops the connector and shuts down its tasks wrapping PUT request to /connectors/<name>/stop
+    pub fn stop_connector(&self, name: &str) -> Result<()> {
+        match self.send(
+            |uri| {
+                self.config
+                    .http_agent
+                    .put(&format!("{}/{}/stop", self.valid_uri(uri), name))
+            },
+            None,
+        ) {
             Ok(_) => Ok(()),
-            Err(ureq::Error::Status(_, r)) => Err(anyhow!("{}", r.into_string()?)),
-            Err(err) => Err(anyhow!("{}", err)),
+            Err(ureq::Error::Status(404, _)) => Err(KofrError::not_found("connector", name)),
+            Err(err) => Err(err.into()),
         }
     }
 
-    pub fn delete_connector(self, name: &str) -> Result<()> {
-        let uri = &self.config.connect_uri;
-        let delete_endpoint = format!("{}/{}/", self.valid_uri(uri), name);
-        match self.config.http_agent.delete(&delete_endpoint).call() {
+    /// resumes a paused or stopped connector wrapping PUT request to /connectors/<name>/resume
+    pub fn resume_connector(&self, name: &str) -> Result<()> {
+        match self.send(
+            |uri| {
+                self.config
+                    .http_agent
+                    .put(&format!("{}/{}/resume", self.valid_uri(uri), name))
+            },
+            None,
+        ) {
+            Ok(_) => Ok(()),
+            Err(ureq::Error::Status(404, _)) => Err(KofrError::not_found("connector", name)),
+            Err(err) => Err(err.into()),
+        }
+    }
+
+    /// deletes a connector and its config wrapping DELETE request to /connectors/<name>
+    pub fn delete_connector(&self, name: &str) -> Result<()> {
+        self.snapshot(name, Change::Delete);
+        match self.send(
+            |uri| {
+                self.config
+                    .http_agent
+                    .delete(&format!("{}/{}/", self.valid_uri(uri), name))
+            },
+            None,
+        ) {
             Ok(_) => Ok(()),
-            Err(ureq::Error::Status(_, r)) => Err(anyhow!("{}", r.into_string()?)),
-            Err(err) => Err(anyhow!("{err}")),
+            Err(ureq::Error::Status(404, _)) => Err(KofrError::not_found("connector", name)),
+            Err(err) => Err(err.into()),
         }
     }
 
-    fn valid_uri(&self, uri: &str) -> String {
+    /// wraps GET request to /connectors/<name>/offsets
+    pub fn get_connector_offsets(&self, name: &str) -> Result<ConnectorOffsets> {
+        match self.send(
+            |uri| {
+                self.config
+                    .http_agent
+                    .get(&format!("{}/{}/offsets", self.valid_uri(uri), name))
+                    .set("Accept", "application/json")
+            },
+            None,
+        ) {
+            Ok(response) => Ok(response.into_json()?),
+            Err(ureq::Error::Status(404, _)) => Err(KofrError::not_found("connector", name)),
+            Err(err) => Err(err.into()),
+        }
+    }
+
+    /// alters offsets of a stopped connector wrapping PATCH request to /connectors/<name>/offsets
+    pub fn alter_connector_offsets(
+        &self,
+        name: &str,
+        offsets: &ConnectorOffsets,
+    ) -> Result<OffsetsResponse> {
+        match self.send(
+            |uri| {
+                self.config
+                    .http_agent
+                    .patch(&format!("{}/{}/offsets", self.valid_uri(uri), name))
+                    .set("Accept", "application/json")
+                    .set("Content-Type", "application/json")
+            },
+            Some(&serde_json::to_value(offsets)?),
+        ) {
+            Ok(response) => Ok(response.into_json()?),
+            Err(ureq::Error::Status(404, _)) => Err(KofrError::not_found("connector", name)),
+            Err(err) => Err(err.into()),
+        }
+    }
+
+    /// resets all offsets of a stopped connector wrapping DELETE request to /connectors/<name>/offsets
+    pub fn reset_connector_offsets(&self, name: &str) -> Result<OffsetsResponse> {
+        match self.send(
+            |uri| {
+                self.config
+                    .http_agent
+                    .delete(&format!("{}/{}/offsets", self.valid_uri(uri), name))
+                    .set("Accept", "application/json")
+            },
+            None,
+        ) {
+            Ok(response) => Ok(response.into_json()?),
+            Err(ureq::Error::Status(404, _)) => Err(KofrError::not_found("connector", name)),
+            Err(err) => Err(err.into()),
+        }
+    }
+
+    /// wraps GET request to /admin/loggers
+    pub fn list_loggers(&self) -> Result<Loggers> {
+        match self.send(
+            |uri| {
+                self.config
+                    .http_agent
+                    .get(&self.loggers_uri(uri))
+                    .set("Accept", "application/json")
+            },
+            None,
+        ) {
+            Ok(response) => Ok(response.into_json()?),
+            Err(err) => Err(err.into()),
+        }
+    }
+
+    /// wraps GET request to /admin/loggers/<name>
+    pub fn get_logger(&self, name: &str) -> Result<LoggerLevel> {
+        match self.send(
+            |uri| {
+                self.config
+                    .http_agent
+                    .get(&format!("{}/{}", self.loggers_uri(uri), name))
+                    .set("Accept", "application/json")
+            },
+            None,
+        ) {
+            Ok(response) => Ok(response.into_json()?),
+            Err(ureq::Error::Status(404, _)) => Err(KofrError::not_found("logger", name)),
+            Err(err) => Err(err.into()),
+        }
+    }
+
+    /// sets a logger's level wrapping PUT request to /admin/loggers/<name>,
+    /// returns the affected loggers for worker scope, cluster scoped changes are applied asynchronously
+    pub fn set_logger_level(&self, name: &str, level: &str, scope: Scope) -> Result<Vec<String>> {
+        match self.send(
+            |uri| {
+                self.config
+                    .http_agent
+                    .put(&format!("{}/{}", self.loggers_uri(uri), name))
+                    .set("Accept", "application/json")
+                    .set("Content-Type", "application/json")
+                    .query("scope", &scope.to_string())
+            },
+            Some(&serde_json::json!({ "level": level })),
+        ) {
+            Ok(response) => match response.status() {
+                204 => Ok(Vec::new()),
+                _ => Ok(response.into_json()?),
+            },
+            Err(err) => Err(err.into()),
+        }
+    }
+
+    /// sends a request built for the current host, following the client's retry policy:
+    /// requests refused while the workers are rebalancing are retried after a backoff, and with
+    /// failover enabled, requests that never reached the host are retried on the next hosts of
+    /// the cluster, and so are idempotent requests failing in flight. Every host of the cluster is
+    /// tried once for requests that never reached their host, even without retries.
+    // keeps ureq's error so that callers match on the status codes like with `call`
+    #[allow(clippy::result_large_err)]
+    pub(crate) fn send<F>(
+        &self,
+        request: F,
+        body: Option<&serde_json::Value>,
+    ) -> std::result::Result<ureq::Response, Error>
+    where
+        F: Fn(&str) -> ureq::Request,
+    {
+        let mut retry = 0;
+        let mut unreached = 0;
+        let mut failed_over = false;
+        loop {
+            let uri = self.current_uri();
+            let request = request(&uri);
+            let idempotent = matches!(request.method(), "GET" | "HEAD" | "PUT" | "DELETE");
+            let result = match body {
+                Some(body) => request.send_json(body),
+                None => request.call(),
+            };
+            let policy = self.retry.as_ref().filter(|p| retry < p.retries);
+            match (result, self.failover.as_ref(), policy) {
+                (Err(Error::Transport(transport)), Some(failover), _)
+                    if never_reached(&transport) && unreached + 1 < failover.hosts.len() =>
+                {
+                    unreached += 1;
+                    self.fail_over(failover, &uri, &transport, Duration::ZERO);
+                    failed_over = true;
+                }
+                (Err(Error::Status(409, response)), _, Some(policy)) => {
+                    let message = response.into_string().unwrap_or_default();
+                    if !is_rebalancing(&message) {
+                        return Err(Error::Status(
+                            409,
+                            ureq::Response::new(409, "Conflict", &message)?,
+                        ));
+                    }
+                    retry += 1;
+                    let backoff = policy.backoff(retry);
+                    self.notify(Retrying::Rebalancing {
+                        backoff,
+                        retry,
+                        retries: policy.retries,
+                    });
+                    std::thread::sleep(backoff);
+                }
+                (Err(Error::Transport(transport)), Some(failover), Some(policy))
+                    if idempotent || never_reached(&transport) =>
+                {
+                    retry += 1;
+                    self.fail_over(failover, &uri, &transport, policy.backoff(retry));
+                    failed_over = true;
+                }
+                (result, failover, _) => {
+                    let reached = !matches!(result, Err(Error::Transport(_)));
+                    if let Some(failover) =
+                        failover.filter(|_| self.host_cache && failed_over && reached)
+                    {
+                        crate::config::remember_healthy_host(&failover.cluster, &uri);
+                    }
+                    return result;
+                }
+            }
+        }
+    }
+
+    /// moves on to the host following the given one after a backoff
+    fn fail_over(
+        &self,
+        failover: &Failover,
+        uri: &str,
+        transport: &ureq::Transport,
+        backoff: Duration,
+    ) {
+        let next = failover.next_host(uri);
+        self.notify(Retrying::Failover {
+            host: uri,
+            next: &next,
+            error: transport.to_string(),
+        });
+        std::thread::sleep(backoff);
+        *self.current_uri.write().unwrap_or_else(|e| e.into_inner()) = next;
+    }
+
+    fn notify(&self, retrying: Retrying) {
+        if let Some(callback) = &self.retry_callback {
+            callback(&retrying);
+        }
+    }
+
+    /// host the next request is sent to, changes when failing over to another host
+    pub fn current_uri(&self) -> String {
+        self.current_uri
+            .read()
+            .unwrap_or_else(|e| e.into_inner())
+            .clone()
+    }
+
+    /// keeps the current config of a connector in the history before it is changed, failing to do
+    /// so only warns as the change itself must not be held up
+    fn snapshot(&self, name: &str, change: Change) {
+        let Some(history) = &self.history else {
+            return;
+        };
+        let recorded = match self.get_connector_config(name) {
+            Ok(config) => history.record(name, change, config),
+            Err(KofrError::NotFound { .. }) => return,
+            Err(err) => Err(err),
+        };
+        if let Err(err) = recorded {
+            eprintln!(
+                "could not snapshot the config of connector: \"{}\": {:#}",
+                name, err
+            );
+        }
+    }
+
+    fn loggers_uri(&self, uri: &str) -> String {
+        if uri.ends_with('/') {
+            return format!("{}admin/loggers", uri);
+        }
+        format!("{}/admin/loggers", uri)
+    }
+
+    pub(crate) fn valid_uri(&self, uri: &str) -> String {
         if uri.ends_with('/') {
             return format!("{}connectors", uri);
         }
         format!("{}/connectors", uri)
     }
 }
+
+/// The agent sending the requests of a client and the host they are first sent to
 pub struct HTTPClientConfig {
     pub http_agent: Agent,
     pub connect_uri: String,
 }
 
+/// parses the body of GET request to /connectors?expand=status&expand=info
+pub(crate) fn verbose_connectors(body: &str) -> Result<Vec<VerboseConnector>> {
+    if body == "[]" {
+        return Ok(Vec::new());
+    }
+
+    let expanded: BTreeMap<String, ExpandedConnector> = serde_json::from_str(body)?;
+    Ok(expanded
+        .into_iter()
+        .map(|(name, expanded)| VerboseConnector {
+            name: ConnectorName(name),
+            tasks: expanded.status.tasks.len(),
+            state: expanded.status.connector_state.state,
+            connector_type: expanded.status.connector_type,
+            worker_id: expanded.status.connector_state.worker_id,
+            connector_class: expanded
+                .info
+                .and_then(|info| info.config.get("connector.class").cloned())
+                .unwrap_or_else(|| "-".to_string()),
+        })
+        .collect())
+}
+
+impl Display for Retrying<'_> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Retrying::Rebalancing {
+                backoff,
+                retry,
+                retries,
+            } => write!(
+                f,
+                "cluster is rebalancing, retrying in {} ({}/{})",
+                humantime::format_duration(*backoff),
+                retry,
+                retries
+            ),
+            Retrying::Failover { host, next, error } => {
+                write!(
+                    f,
+                    "host \"{}\" failed: {}, retrying on \"{}\"",
+                    host, error, next
+                )
+            }
+        }
+    }
+}
+
+/// Messages of the `409 Conflict` responses returned by workers while the group rebalances
+const REBALANCE_MESSAGES: [&str; 3] = ["rebalance", "stale configuration", "conflicting operation"];
+
+/// Whether a `409 Conflict` response was caused by a rebalance rather than a conflicting
+/// resource, like a connector that already exists
+fn is_rebalancing(message: &str) -> bool {
+    let message = message.to_lowercase();
+    REBALANCE_MESSAGES.iter().any(|m| message.contains(m))
+}
+
+/// whether a request failed before reaching its host, so it can safely be sent to another one
+fn never_reached(transport: &ureq::Transport) -> bool {
+    matches!(
+        transport.kind(),
+        ureq::ErrorKind::ConnectionFailed | ureq::ErrorKind::Dns
+    )
+}
+
+/// Hosts of a cluster a client fails over to when its current host stops answering
+struct Failover {
+    cluster: String,
+    hosts: Vec<String>,
+}
+
+impl Failover {
+    fn new(cluster: &ClusterContext) -> Self {
+        Self {
+            cluster: cluster.name.clone(),
+            hosts: cluster.hosts.clone(),
+        }
+    }
+
+    /// the host following the given one, wrapping around to the first host
+    fn next_host(&self, uri: &str) -> String {
+        let next = self
+            .hosts
+            .iter()
+            .position(|h| h.trim_end_matches('/') == uri.trim_end_matches('/'))
+            .map_or(0, |i| (i + 1) % self.hosts.len());
+        self.hosts
+            .get(next)
+            .cloned()
+            .unwrap_or_else(|| uri.to_string())
+    }
+}
+
+/// agent using the cluster's TLS settings and sending its credentials, if any, with every request
+pub fn cluster_agent(cluster: &ClusterContext) -> Result<Agent> {
+    let builder = ureq::AgentBuilder::new()
+        .timeout_read(Duration::from_secs(5))
+        .timeout_write(Duration::from_secs(5));
+    let tls_config =
+        crate::tls::client_config(cluster).map_err(|err| KofrError::Config(format!("{err:#}")))?;
+    let builder = match tls_config {
+        Some(tls_config) => builder.tls_config(tls_config),
+        None => builder,
+    };
+    let builder = match &cluster.auth {
+        Some(auth) => builder.middleware(Authorization(auth.header()?)),
+        None => builder,
+    };
+    Ok(builder.build())
+}
+
+struct Authorization(String);
+
+impl ureq::Middleware for Authorization {
+    fn handle(
+        &self,
+        request: ureq::Request,
+        next: ureq::MiddlewareNext,
+    ) -> std::result::Result<ureq::Response, Error> {
+        next.handle(request.set("Authorization", &self.0))
+    }
+}
+
 pub type ConnectorConfig = HashMap<String, String>;
 
 #[derive(Debug, Serialize, Deserialize, PartialEq, Clone)]
@@ -354,6 +741,8 @@ pub struct Connector {
 pub struct ConnectorState {
     pub state: State,
     pub worker_id: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub trace: Option<String>,
 }
 
 #[derive(Debug, Serialize, Deserialize)]
@@ -366,6 +755,13 @@ pub struct ConnectorStatus {
     pub connector_type: ConnectorType,
 }
 
+/// A connector listed by GET request to /connectors?expand=status&expand=info
+#[derive(Debug, Deserialize)]
+struct ExpandedConnector {
+    status: ConnectorStatus,
+    info: Option<Connector>,
+}
+
 #[derive(Debug, Serialize, Deserialize)]
 pub struct DescribeConnector {
     pub name: ConnectorName,
@@ -377,14 +773,14 @@ pub struct DescribeConnector {
     pub connector_type: ConnectorType,
 }
 
-#[derive(Debug, Serialize, Deserialize, PartialEq)]
+#[derive(Debug, Serialize, Deserialize, PartialEq, Clone)]
 #[serde(rename_all = "lowercase")]
 pub enum ConnectorType {
     Sink,
     Source,
 }
 
-#[derive(tabled::Tabled, Debug)]
+#[derive(tabled::Tabled, Debug, Serialize)]
 pub struct VerboseConnector {
     #[tabled(rename = "NAME")]
     pub name: ConnectorName,
@@ -393,19 +789,57 @@ pub struct VerboseConnector {
     #[tabled(rename = "TASKS")]
     pub tasks: usize,
     #[tabled(rename = "TYPE")]
+    #[serde(rename = "type")]
     pub connector_type: ConnectorType,
     #[tabled(rename = "WORKER_ID")]
     pub worker_id: String,
+    #[tabled(rename = "CLASS")]
+    #[serde(rename = "class")]
+    pub connector_class: String,
 }
 
-#[derive(Debug, Serialize, Deserialize, PartialEq)]
-#[serde(rename_all = "UPPERCASE")]
+impl ConnectorStatus {
+    /// failure descriptions of the connector and its failed tasks, including their traces
+    pub fn failures(&self) -> Vec<String> {
+        let describe = |what: String, worker_id: &str, trace: &Option<String>| {
+            format!(
+                "{} failed on worker {}:\n{}",
+                what,
+                worker_id,
+                trace.as_deref().unwrap_or("no trace reported")
+            )
+        };
+        let mut failures = Vec::new();
+        if self.connector_state.state == State::Failed {
+            failures.push(describe(
+                format!("connector: \"{}\"", self.name),
+                &self.connector_state.worker_id,
+                &self.connector_state.trace,
+            ));
+        }
+        for task in self.tasks.iter().filter(|t| t.state == TaskState::Failed) {
+            failures.push(describe(
+                format!("task: '{}/{}'", self.name, task.id),
+                &task.worker_id,
+                &task.trace,
+            ));
+        }
+        failures
+    }
+}
+
+/// Connector state reported by the cluster. States unknown to kofr, e.g. introduced
+/// by newer connect versions, are kept as `Unknown` instead of failing to parse.
+#[derive(Debug, Serialize, Deserialize, PartialEq, Clone)]
+#[serde(from = "String", into = "String")]
 pub enum State {
     Running,
     Failed,
     Unassigned,
     Paused,
     Restarting,
+    Stopped,
+    Unknown(String),
 }
 
 impl From<&CreateConnector> for Connector {
@@ -440,13 +874,26 @@ impl std::str::FromStr for State {
             "UNASSIGNED" => Ok(State::Unassigned),
             "FAILED" => Ok(State::Failed),
             "RESTARTING" => Ok(State::Restarting),
+            "STOPPED" => Ok(State::Stopped),
             _ => Err(anyhow!(
-                "unimplemneted state, valid values are: RUNNING, PAUSED, UNASSIGNED, RESTARTING and FAILED"
+                "unimplemneted state, valid values are: RUNNING, PAUSED, UNASSIGNED, RESTARTING, STOPPED and FAILED"
             )),
         }
     }
 }
 
+impl From<String> for State {
+    fn from(state: String) -> Self {
+        State::from_str(&state).unwrap_or(State::Unknown(state))
+    }
+}
+
+impl From<State> for String {
+    fn from(state: State) -> Self {
+        state.to_string()
+    }
+}
+
 impl Display for ConnectorName {
     fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
         write!(f, "{}", self.0)
@@ -461,6 +908,8 @@ impl Display for State {
             Self::Paused => write!(f, "PAUSED"),
             Self::Unassigned => write!(f, "UNASSIGNED"),
             Self::Restarting => write!(f, "RESTARTING"),
+            Self::Stopped => write!(f, "STOPPED"),
+            Self::Unknown(state) => write!(f, "{state}"),
         }
     }
 }
@@ -492,7 +941,6 @@ impl std::str::FromStr for ConnectorType {
 mod tests {
     use super::*;
     use kcmockserver::KcTestServer;
-    use std::time::Duration;
 
     #[test]
     fn test_listing_connectors_should_return_empty_vec() {
@@ -573,8 +1021,8 @@ mod tests {
             }
         }"#;
 
-        let a = serde_json::from_str(&a).unwrap();
-        let b = serde_json::from_str(&b).unwrap();
+        let a = serde_json::from_str(a).unwrap();
+        let b = serde_json::from_str(b).unwrap();
 
         client.create_connector(&a).unwrap();
         client.create_connector(&b).unwrap();
@@ -619,6 +1067,97 @@ mod tests {
         assert_eq!(connectors_vec[0].state, State::Running);
     }
 
+    #[test]
+    fn test_failures_of_failed_tasks() {
+        let status = r#"
+        {
+            "name": "orders-sink",
+            "connector": {"state": "RUNNING", "worker_id": "10.0.0.1:8083"},
+            "tasks": [
+                {"id": 0, "state": "RUNNING", "worker_id": "10.0.0.1:8083"},
+                {"id": 1, "state": "FAILED", "worker_id": "10.0.0.2:8083", "trace": "boom"}
+            ],
+            "type": "sink"
+        }"#;
+        let mut status: ConnectorStatus = serde_json::from_str(status).unwrap();
+
+        assert_eq!(
+            status.failures(),
+            vec!["task: 'orders-sink/1' failed on worker 10.0.0.2:8083:\nboom".to_string()]
+        );
+
+        status.tasks.pop();
+        assert!(status.failures().is_empty());
+    }
+
+    #[test]
+    fn test_parsing_stopped_and_unknown_states() {
+        let state: State = serde_json::from_str(r#""STOPPED""#).unwrap();
+        assert_eq!(state, State::Stopped);
+
+        let state: State = serde_json::from_str(r#""DRAINING""#).unwrap();
+        assert_eq!(state, State::Unknown("DRAINING".to_string()));
+        assert_eq!(state.to_string(), "DRAINING");
+
+        assert!(State::from_str("DRAINING").is_err());
+    }
+
+    #[test]
+    fn test_failing_over_to_the_next_host() {
+        let failover = Failover {
+            cluster: "dev".to_string(),
+            hosts: vec![
+                "http://a:8083".to_string(),
+                "http://b:8083".to_string(),
+                "http://c:8083".to_string(),
+            ],
+        };
+
+        assert_eq!(failover.next_host("http://a:8083/"), "http://b:8083");
+        assert_eq!(failover.next_host("http://c:8083"), "http://a:8083");
+        assert_eq!(failover.next_host("http://unknown:8083"), "http://a:8083");
+    }
+
+    #[test]
+    fn test_failing_over_from_a_dead_host_without_retries() {
+        let mut server = mockito::Server::new();
+        let listed = server
+            .mock("GET", "/connectors")
+            .with_body(r#"["users-sink"]"#)
+            .expect(1)
+            .create();
+        // nothing listens on port 1, like a cached host that went down since the last run
+        let dead_host = "http://127.0.0.1:1".to_string();
+        let cluster = ClusterContext {
+            name: "dev".to_string(),
+            hosts: vec![dead_host.clone(), server.url()],
+            ..Default::default()
+        };
+        let client = HTTPClient::from_config(HTTPClientConfig {
+            http_agent: ureq::agent(),
+            connect_uri: dead_host,
+        })
+        .with_retry(None)
+        .with_failover(&cluster);
+
+        assert_eq!(client.list_connectors().unwrap(), vec!["users-sink"]);
+        assert_eq!(client.current_uri(), server.url());
+        listed.assert();
+    }
+
+    #[test]
+    fn test_retrying_only_rebalance_conflicts() {
+        assert!(is_rebalancing(
+            r#"{"error_code":409,"message":"Cannot complete request momentarily due to stale configuration (typically caused by a concurrent config change)"}"#
+        ));
+        assert!(is_rebalancing(
+            r#"{"error_code":409,"message":"Request cannot be completed because a rebalance is expected"}"#
+        ));
+        assert!(!is_rebalancing(
+            r#"{"error_code":409,"message":"Connector users-sink already exists"}"#
+        ));
+    }
+
     #[test]
     fn test_listing_empty_connector_status() {
         let server = KcTestServer::new();
@@ -636,4 +1175,194 @@ mod tests {
 
         assert_eq!(connectors.len(), 0);
     }
+
+    fn mock_client(server: &mockito::Server) -> HTTPClient {
+        let agent: Agent = ureq::AgentBuilder::new()
+            .timeout_read(Duration::from_secs(5))
+            .timeout_write(Duration::from_secs(5))
+            .build();
+
+        HTTPClient::from_config(HTTPClientConfig {
+            http_agent: agent,
+            connect_uri: server.url(),
+        })
+    }
+
+    #[test]
+    fn test_reporting_retries_of_rebalance_conflicts() {
+        let mut server = mockito::Server::new();
+        let rebalancing = server
+            .mock("GET", "/connectors")
+            .with_status(409)
+            .with_body(r#"{"error_code": 409, "message": "Request cannot be completed because a rebalance is expected"}"#)
+            .expect(1)
+            .create();
+        let listed = server
+            .mock("GET", "/connectors")
+            .with_body(r#"["users-sink"]"#)
+            .expect(1)
+            .create();
+        let retries = std::sync::Arc::new(std::sync::Mutex::new(Vec::new()));
+        let reported = retries.clone();
+        let client = mock_client(&server)
+            .with_retry(Some(RetryPolicy {
+                retries: 2,
+                backoff: Duration::from_millis(1),
+                max_backoff: Duration::from_millis(1),
+            }))
+            .with_retry_callback(move |retrying| {
+                reported.lock().unwrap().push(retrying.to_string())
+            });
+
+        assert_eq!(client.list_connectors().unwrap(), vec!["users-sink"]);
+        assert_eq!(
+            *retries.lock().unwrap(),
+            vec!["cluster is rebalancing, retrying in 1ms (1/2)"]
+        );
+        rebalancing.assert();
+        listed.assert();
+    }
+
+    #[test]
+    fn test_setting_logger_level_sends_the_scope() {
+        let mut server = mockito::Server::new();
+        let worker = server
+            .mock("PUT", "/admin/loggers/org.apache.kafka.connect")
+            .match_query(mockito::Matcher::UrlEncoded(
+                "scope".to_string(),
+                "worker".to_string(),
+            ))
+            .match_body(mockito::Matcher::Json(
+                serde_json::json!({"level": "DEBUG"}),
+            ))
+            .with_status(200)
+            .with_body(r#"["org.apache.kafka.connect", "org.apache.kafka.connect.runtime"]"#)
+            .create();
+        let cluster = server
+            .mock("PUT", "/admin/loggers/org.apache.kafka.connect")
+            .match_query(mockito::Matcher::UrlEncoded(
+                "scope".to_string(),
+                "cluster".to_string(),
+            ))
+            .with_status(204)
+            .create();
+        let client = mock_client(&server);
+
+        let affected = client
+            .set_logger_level("org.apache.kafka.connect", "DEBUG", Scope::Worker)
+            .unwrap();
+        assert_eq!(
+            affected,
+            vec![
+                "org.apache.kafka.connect".to_string(),
+                "org.apache.kafka.connect.runtime".to_string()
+            ]
+        );
+        // cluster scoped changes are applied asynchronously and return no body
+        let affected = client
+            .set_logger_level("org.apache.kafka.connect", "DEBUG", Scope::Cluster)
+            .unwrap();
+        assert!(affected.is_empty());
+        worker.assert();
+        cluster.assert();
+    }
+
+    #[test]
+    fn test_getting_loggers() {
+        let mut server = mockito::Server::new();
+        server
+            .mock("GET", "/admin/loggers")
+            .with_body(r#"{"org.reflections": {"level": "ERROR"}, "root": {"level": "INFO", "last_modified": 1696204800000}}"#)
+            .create();
+        server
+            .mock("GET", "/admin/loggers/missing")
+            .with_status(404)
+            .with_body(r#"{"error_code": 404, "message": "Logger missing not found."}"#)
+            .create();
+        let client = mock_client(&server);
+
+        let loggers = client.list_loggers().unwrap();
+        assert_eq!(loggers["root"].last_modified, Some(1696204800000));
+        assert_eq!(loggers["org.reflections"].level, "ERROR");
+        assert!(matches!(
+            client.get_logger("missing"),
+            Err(KofrError::NotFound { .. })
+        ));
+    }
+
+    #[test]
+    fn test_changing_a_missing_connector_is_not_found() {
+        let mut server = mockito::Server::new();
+        let not_found = server
+            .mock(
+                "DELETE",
+                mockito::Matcher::Regex("^/connectors/missing".to_string()),
+            )
+            .with_status(404)
+            .with_body(r#"{"error_code": 404, "message": "Connector missing not found"}"#)
+            .create();
+        server
+            .mock("PUT", "/connectors/missing/pause")
+            .with_status(404)
+            .with_body(r#"{"error_code": 404, "message": "Connector missing not found"}"#)
+            .create();
+        server
+            .mock("GET", "/connectors/missing/topics")
+            .with_status(404)
+            .with_body(r#"{"error_code": 404, "message": "Connector missing not found"}"#)
+            .create();
+        let client = mock_client(&server);
+
+        let err = client.delete_connector("missing").unwrap_err();
+        assert!(matches!(err, KofrError::NotFound { .. }));
+        assert_eq!(
+            crate::error::exit_code(&anyhow::Error::from(err)),
+            exitcode::NOINPUT
+        );
+        not_found.assert();
+        assert!(matches!(
+            client.pause_connector("missing"),
+            Err(KofrError::NotFound { .. })
+        ));
+        assert!(matches!(
+            client.list_topics("missing"),
+            Err(KofrError::NotFound { .. })
+        ));
+    }
+
+    #[test]
+    fn test_changes_are_snapshotted() {
+        let mut server = mockito::Server::new();
+        for name in ["users-sink", "orders-sink"] {
+            server
+                .mock("GET", format!("/connectors/{}/config", name).as_str())
+                .with_body(format!(r#"{{"name": "{}", "tasks.max": "1"}}"#, name))
+                .create();
+        }
+        server
+            .mock("PUT", "/connectors/users-sink/config")
+            .with_body(r#"{"name": "users-sink", "config": {}, "tasks": [], "type": "sink"}"#)
+            .create();
+        server
+            .mock(
+                "DELETE",
+                mockito::Matcher::Regex("^/connectors/orders-sink".to_string()),
+            )
+            .with_status(204)
+            .create();
+        let dir = tempfile::tempdir().unwrap();
+        let history = History::new(dir.path());
+        let client = mock_client(&server).with_history(Some(history.clone()));
+
+        let config: ConnectorConfig = [("tasks.max".to_string(), "2".to_string())].into();
+        client.put_connector("users-sink", config).unwrap();
+        client.delete_connector("orders-sink").unwrap();
+
+        let updated = history.snapshot("users-sink", None).unwrap();
+        assert_eq!(updated.change, Change::Update);
+        assert_eq!(updated.config["tasks.max"], "1");
+        let deleted = history.snapshot("orders-sink", None).unwrap();
+        assert_eq!(deleted.change, Change::Delete);
+        assert_eq!(deleted.config["name"], "orders-sink");
+    }
 }
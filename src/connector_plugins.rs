@@ -1,46 +1,153 @@
 use std::collections::HashMap;
 
-use anyhow::{anyhow, Context, Result};
-
-pub fn list_plugins(host: &str) -> Result<serde_json::Value> {
-    let endpoint = plugins_endpoint(host);
-    match ureq::get(&endpoint)
-        .set("Accept", "application/json")
-        .call()
-    {
-        Ok(response) => response
-            .into_json()
-            .context("invalid json returned from api"),
-        Err(ureq::Error::Status(_, r)) => Err(anyhow!("{}", r.into_string()?)),
-        Err(err) => Err(anyhow!("{}", err)),
+use serde::{Deserialize, Serialize};
+
+use crate::connect::HTTPClient;
+use crate::error::{KofrError, Result};
+
+impl HTTPClient {
+    /// lists the connector plugins installed on the worker wrapping GET request to
+    /// /connector-plugins
+    pub fn list_plugins(&self) -> Result<Vec<ConnectorPlugin>> {
+        match self.send(
+            |uri| {
+                self.config
+                    .http_agent
+                    .get(&self.plugins_uri(uri))
+                    .set("Accept", "application/json")
+            },
+            None,
+        ) {
+            Ok(response) => Ok(response.into_json()?),
+            Err(err) => Err(err.into()),
+        }
     }
+
+    /// validates a connector config against the config definition of a plugin, wrapping PUT
+    /// request to /connector-plugins/<class>/config/validate
+    pub fn validate_config(
+        &self,
+        name: &str,
+        config: HashMap<String, String>,
+    ) -> Result<ConfigValidation> {
+        match self.send(
+            |uri| {
+                self.config
+                    .http_agent
+                    .put(&format!(
+                        "{}/{}/config/validate",
+                        self.plugins_uri(uri),
+                        name
+                    ))
+                    .set("Accept", "application/json")
+                    .set("Content-Type", "application/json")
+            },
+            Some(&serde_json::to_value(config)?),
+        ) {
+            Ok(response) => Ok(response.into_json()?),
+            Err(err) => Err(err.into()),
+        }
+    }
+
+    /// definitions of the config keys of a plugin wrapping GET request to
+    /// /connector-plugins/<class>/config
+    pub fn plugin_config(&self, class: &str) -> Result<Vec<ConfigKey>> {
+        match self.send(
+            |uri| {
+                self.config
+                    .http_agent
+                    .get(&format!("{}/{}/config", self.plugins_uri(uri), class))
+                    .set("Accept", "application/json")
+            },
+            None,
+        ) {
+            Ok(response) => Ok(response.into_json()?),
+            Err(ureq::Error::Status(404, _)) => Err(KofrError::not_found("plugin", class)),
+            Err(err) => Err(err.into()),
+        }
+    }
+
+    fn plugins_uri(&self, uri: &str) -> String {
+        if uri.ends_with('/') {
+            return format!("{}connector-plugins", uri);
+        }
+        format!("{}/connector-plugins", uri)
+    }
+}
+
+#[derive(Debug, Serialize, Deserialize, tabled::Tabled)]
+pub struct ConnectorPlugin {
+    #[tabled(rename = "CLASS")]
+    pub class: String,
+    #[serde(rename = "type")]
+    #[tabled(rename = "TYPE")]
+    pub plugin_type: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    #[tabled(rename = "VERSION")]
+    #[tabled(display_with = "display_option")]
+    pub version: Option<String>,
+}
+
+/// The definition of a config key of a plugin
+#[derive(Debug, Serialize, Deserialize, Default)]
+#[serde(default)]
+pub struct ConfigKey {
+    pub name: String,
+    #[serde(rename = "type")]
+    pub key_type: String,
+    pub required: bool,
+    pub default_value: Option<String>,
+    pub importance: String,
+    pub documentation: Option<String>,
+    pub group: Option<String>,
+}
+
+/// The result of validating a connector config against its plugin
+#[derive(Debug, Serialize, Deserialize)]
+pub struct ConfigValidation {
+    pub name: String,
+    pub error_count: usize,
+    #[serde(default)]
+    pub groups: Vec<String>,
+    pub configs: Vec<ConfigValidationEntry>,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct ConfigValidationEntry {
+    pub definition: ConfigKey,
+    pub value: ConfigValue,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct ConfigValue {
+    pub name: String,
+    pub value: Option<String>,
+    #[serde(default)]
+    pub recommended_values: Vec<String>,
+    #[serde(default)]
+    pub errors: Vec<String>,
+    #[serde(default)]
+    pub visible: bool,
 }
 
-pub fn validate_config(
-    host: &str,
-    name: &str,
-    config: HashMap<String, String>,
-) -> Result<serde_json::Value> {
-    let endpoint = plugins_endpoint(host);
-    let endpoint = format!("{}/{}/config/validate", endpoint, name);
-    // let config = serde_json::to_value(config)?;
-    dbg!(&endpoint);
-    match ureq::put(&endpoint)
-        .set("Accept", "application/json")
-        .set("Content-Type", "application/json")
-        .send_json(config)
-    {
-        Ok(response) => response
-            .into_json()
-            .context("invalid json returned from api"),
-        Err(ureq::Error::Status(_, r)) => Err(anyhow!("{}", r.into_string()?)),
-        Err(err) => Err(anyhow!("{}", err)),
+impl ConfigValidation {
+    /// errors of the invalid config keys, formatted as `key: error`
+    pub fn errors(&self) -> Vec<String> {
+        self.configs
+            .iter()
+            .flat_map(|c| {
+                c.value
+                    .errors
+                    .iter()
+                    .map(move |e| format!("{}: {}", c.value.name, e))
+            })
+            .collect()
     }
 }
 
-fn plugins_endpoint(uri: &str) -> String {
-    if uri.ends_with('/') {
-        return format!("{}connector-plugins", uri);
+fn display_option(o: &Option<String>) -> String {
+    match o {
+        Some(s) => s.to_string(),
+        None => "-".to_string(),
     }
-    format!("{}/connector-plugins", uri)
 }
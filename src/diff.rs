@@ -0,0 +1,181 @@
+use std::collections::BTreeSet;
+use std::fmt::Write;
+use std::io::IsTerminal;
+
+use serde::Serialize;
+use similar::{ChangeTag, TextDiff};
+
+use kofr::connect::ConnectorConfig;
+
+const RED: &str = "\x1b[31m";
+const GREEN: &str = "\x1b[32m";
+const YELLOW: &str = "\x1b[33m";
+const CYAN: &str = "\x1b[36m";
+const RESET: &str = "\x1b[0m";
+
+#[derive(Debug, PartialEq, Serialize)]
+#[serde(tag = "change", rename_all = "lowercase")]
+pub enum KeyChange {
+    Added {
+        key: String,
+        value: String,
+    },
+    Removed {
+        key: String,
+        value: String,
+    },
+    Changed {
+        key: String,
+        old: String,
+        new: String,
+    },
+}
+
+/// Key level difference between two connector configs, ordered by key
+#[derive(Debug, PartialEq, Serialize)]
+#[serde(transparent)]
+pub struct ConfigDiff {
+    pub changes: Vec<KeyChange>,
+}
+
+impl ConfigDiff {
+    pub fn new(old: &ConnectorConfig, new: &ConnectorConfig) -> Self {
+        let keys: BTreeSet<&String> = old.keys().chain(new.keys()).collect();
+        let changes = keys
+            .into_iter()
+            .filter_map(|key| match (old.get(key), new.get(key)) {
+                (None, Some(value)) => Some(KeyChange::Added {
+                    key: key.clone(),
+                    value: value.clone(),
+                }),
+                (Some(value), None) => Some(KeyChange::Removed {
+                    key: key.clone(),
+                    value: value.clone(),
+                }),
+                (Some(old), Some(new)) if old != new => Some(KeyChange::Changed {
+                    key: key.clone(),
+                    old: old.clone(),
+                    new: new.clone(),
+                }),
+                _ => None,
+            })
+            .collect();
+        Self { changes }
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.changes.is_empty()
+    }
+
+    /// renders one line per added, removed or changed key
+    pub fn key_level(&self, color: bool) -> String {
+        let mut out = String::new();
+        for change in &self.changes {
+            let _ = match change {
+                KeyChange::Added { key, value } => {
+                    writeln!(out, "{}", paint(&format!("+ {key}: {value}"), GREEN, color))
+                }
+                KeyChange::Removed { key, value } => {
+                    writeln!(out, "{}", paint(&format!("- {key}: {value}"), RED, color))
+                }
+                KeyChange::Changed { key, old, new } => writeln!(
+                    out,
+                    "{}",
+                    paint(&format!("~ {key}: {old} -> {new}"), YELLOW, color)
+                ),
+            };
+        }
+        out
+    }
+}
+
+/// renders a unified diff of the two configs as pretty, key sorted json
+pub fn unified(
+    old: &ConnectorConfig,
+    new: &ConnectorConfig,
+    old_header: &str,
+    new_header: &str,
+    color: bool,
+) -> String {
+    let old = pretty_sorted(old);
+    let new = pretty_sorted(new);
+    let diff = TextDiff::from_lines(&old, &new);
+
+    let mut out = String::new();
+    let _ = writeln!(out, "{}", paint(&format!("--- {old_header}"), RED, color));
+    let _ = writeln!(out, "{}", paint(&format!("+++ {new_header}"), GREEN, color));
+    for hunk in diff.unified_diff().iter_hunks() {
+        let _ = writeln!(out, "{}", paint(&hunk.header().to_string(), CYAN, color));
+        for change in hunk.iter_changes() {
+            let line = change.to_string_lossy();
+            let line = line.trim_end_matches('\n');
+            let _ = match change.tag() {
+                ChangeTag::Delete => writeln!(out, "{}", paint(&format!("-{line}"), RED, color)),
+                ChangeTag::Insert => {
+                    writeln!(out, "{}", paint(&format!("+{line}"), GREEN, color))
+                }
+                ChangeTag::Equal => writeln!(out, " {line}"),
+            };
+        }
+    }
+    out
+}
+
+/// whether diffs written to stdout should be colored
+pub fn use_color() -> bool {
+    std::io::stdout().is_terminal() && std::env::var_os("NO_COLOR").is_none()
+}
+
+fn pretty_sorted(config: &ConnectorConfig) -> String {
+    let sorted: std::collections::BTreeMap<&String, &String> = config.iter().collect();
+    let mut pretty = serde_json::to_string_pretty(&sorted).unwrap_or_default();
+    pretty.push('\n');
+    pretty
+}
+
+fn paint(text: &str, color_code: &str, color: bool) -> String {
+    if color {
+        return format!("{color_code}{text}{RESET}");
+    }
+    text.to_string()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn config(pairs: &[(&str, &str)]) -> ConnectorConfig {
+        pairs
+            .iter()
+            .map(|(k, v)| (k.to_string(), v.to_string()))
+            .collect()
+    }
+
+    #[test]
+    fn test_key_level_diff() {
+        let old = config(&[("tasks.max", "1"), ("topics", "a"), ("name", "s")]);
+        let new = config(&[("tasks.max", "4"), ("name", "s"), ("batch.size", "10")]);
+
+        let diff = ConfigDiff::new(&old, &new);
+        assert_eq!(
+            diff.key_level(false),
+            "+ batch.size: 10\n~ tasks.max: 1 -> 4\n- topics: a\n"
+        );
+    }
+
+    #[test]
+    fn test_identical_configs_have_no_diff() {
+        let old = config(&[("tasks.max", "1")]);
+        assert!(ConfigDiff::new(&old, &old).is_empty());
+    }
+
+    #[test]
+    fn test_unified_diff() {
+        let old = config(&[("tasks.max", "1"), ("topics", "a")]);
+        let new = config(&[("tasks.max", "2"), ("topics", "a")]);
+
+        let diff = unified(&old, &new, "live", "local", false);
+        assert!(diff.contains("-  \"tasks.max\": \"1\","));
+        assert!(diff.contains("+  \"tasks.max\": \"2\","));
+    }
+}
@@ -1,17 +1,154 @@
+use serde::Deserialize;
 use thiserror::Error;
 
 pub type Result<T> = std::result::Result<T, KofrError>;
 
+/// Errors kofr can tell apart, each one exiting with its own code, see `KofrError::exit_code`.
+/// Anything else exits with 1.
 #[derive(Error, Debug)]
 pub enum KofrError {
-    #[error("connector with name: {0} was not found")]
-    ConnectorNotFound(String),
-    #[error("could not deserialize the json returned")]
-    DeserializeConnectorError(#[from] serde_json::Error),
-    #[error("Response returned from {0} is not a json array")]
-    NotAJsonArrayError(String),
-    #[error("error while requesting uri, {0}")]
-    ApiError(#[from] ureq::Error),
+    /// an error body returned by Connect, `{"error_code": 500, "message": "..."}`
+    #[error("{message}")]
+    Connect { error_code: u16, message: String },
+    #[error("{resource}: \"{name}\" was not found")]
+    NotFound {
+        resource: &'static str,
+        name: String,
+    },
+    /// the resource already exists or is being changed concurrently
     #[error("{0}")]
-    IoError(#[from] std::io::Error),
+    Conflict(String),
+    /// the cluster rejected the configured credentials
+    #[error("{0}")]
+    Auth(String),
+    /// the cluster could not be reached
+    #[error("{0}")]
+    Transport(String),
+    /// the cluster rejected a connector config or a request as invalid
+    #[error("{0}")]
+    Validation(String),
+    /// the kofr config or a cluster's configuration is invalid, e.g. unreadable TLS files
+    #[error("{0}")]
+    Config(String),
+    /// a local file kofr keeps could not be read or written, e.g. a history snapshot
+    #[error("{0}")]
+    Io(String),
+    /// the response could not be read or parsed
+    #[error("invalid response: {0}")]
+    Response(#[from] std::io::Error),
+    #[error("invalid json: {0}")]
+    Json(#[from] serde_json::Error),
+}
+
+/// The body of Connect's error responses
+#[derive(Deserialize)]
+struct ErrorBody {
+    error_code: u16,
+    message: String,
+}
+
+impl KofrError {
+    pub fn not_found(resource: &'static str, name: impl Into<String>) -> Self {
+        KofrError::NotFound {
+            resource,
+            name: name.into(),
+        }
+    }
+
+    /// the process exit code for this error:
+    ///
+    /// | error      | code |
+    /// |------------|------|
+    /// | Validation | 65   |
+    /// | NotFound   | 66   |
+    /// | Transport  | 69   |
+    /// | Conflict   | 73   |
+    /// | Io         | 74   |
+    /// | Connect    | 76   |
+    /// | Response   | 76   |
+    /// | Json       | 76   |
+    /// | Auth       | 77   |
+    /// | Config     | 78   |
+    pub fn exit_code(&self) -> exitcode::ExitCode {
+        match self {
+            KofrError::Validation(_) => exitcode::DATAERR,
+            KofrError::NotFound { .. } => exitcode::NOINPUT,
+            KofrError::Transport(_) => exitcode::UNAVAILABLE,
+            KofrError::Conflict(_) => exitcode::CANTCREAT,
+            KofrError::Io(_) => exitcode::IOERR,
+            KofrError::Connect { .. } | KofrError::Response(_) | KofrError::Json(_) => {
+                exitcode::PROTOCOL
+            }
+            KofrError::Auth(_) => exitcode::NOPERM,
+            KofrError::Config(_) => exitcode::CONFIG,
+        }
+    }
+
+    /// builds the error of a response with the given status and body, bodies that are not
+    /// Connect errors are kept as the message
+    pub fn from_response(status: u16, body: &str) -> Self {
+        let (error_code, message) = match serde_json::from_str::<ErrorBody>(body) {
+            Ok(body) => (body.error_code, body.message),
+            Err(_) => (status, body.trim().to_string()),
+        };
+        match status {
+            400 | 422 => KofrError::Validation(message),
+            401 | 403 => KofrError::Auth(message),
+            409 => KofrError::Conflict(message),
+            _ => KofrError::Connect {
+                error_code,
+                message,
+            },
+        }
+    }
+}
+
+impl From<ureq::Error> for KofrError {
+    fn from(err: ureq::Error) -> Self {
+        match err {
+            ureq::Error::Status(status, response) => {
+                let body = response.into_string().unwrap_or_default();
+                KofrError::from_response(status, &body)
+            }
+            ureq::Error::Transport(transport) => KofrError::Transport(transport.to_string()),
+        }
+    }
+}
+
+/// the exit code of an error returned by a command, found anywhere in its chain of causes
+pub fn exit_code(err: &anyhow::Error) -> exitcode::ExitCode {
+    // errors used as context are not part of the chain as their own type
+    err.downcast_ref::<KofrError>()
+        .or_else(|| {
+            err.chain()
+                .find_map(|cause| cause.downcast_ref::<KofrError>())
+        })
+        .map_or(1, KofrError::exit_code)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_mapping_connect_error_bodies() {
+        let err = KofrError::from_response(
+            409,
+            r#"{"error_code":409,"message":"Connector users-sink already exists"}"#,
+        );
+        assert!(matches!(err, KofrError::Conflict(_)));
+        assert_eq!(err.to_string(), "Connector users-sink already exists");
+
+        let err = KofrError::from_response(500, "upstream connect error");
+        assert_eq!(err.exit_code(), exitcode::PROTOCOL);
+        assert_eq!(err.to_string(), "upstream connect error");
+
+        let err = anyhow::Error::from(KofrError::not_found("connector", "users-sink"))
+            .context("could not describe connector");
+        assert_eq!(exit_code(&err), exitcode::NOINPUT);
+        let err = anyhow::anyhow!("connection refused")
+            .context(KofrError::Transport("out of hosts".to_string()));
+        assert_eq!(exit_code(&err), exitcode::UNAVAILABLE);
+        assert_eq!(exit_code(&anyhow::anyhow!("boom")), 1);
+    }
 }
@@ -0,0 +1,272 @@
+use std::fmt::Display;
+use std::io::Write;
+use std::path::{Path, PathBuf};
+use std::time::SystemTime;
+
+use home::home_dir;
+use serde::{Deserialize, Serialize};
+
+use crate::connect::ConnectorConfig;
+use crate::error::{KofrError, Result};
+
+/// Snapshots kept per connector, the oldest ones are removed first
+pub const MAX_SNAPSHOTS: usize = 50;
+
+/// Local store of the configs connectors had before kofr changed or deleted them. Every connector
+/// has its own directory under the cluster's one, holding a json file per snapshot numbered
+/// from 1. Snapshots hold secrets of the configs, so on unix only the user can read them.
+#[derive(Debug, Clone)]
+pub struct History {
+    dir: PathBuf,
+}
+
+/// A connector config as it was before a change
+#[derive(Debug, Serialize, Deserialize, PartialEq)]
+#[serde(rename_all = "kebab-case")]
+pub struct Snapshot {
+    pub revision: usize,
+    pub taken_at: String,
+    pub change: Change,
+    pub config: ConnectorConfig,
+}
+
+/// The change that replaced a snapshotted config
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq)]
+#[serde(rename_all = "lowercase")]
+pub enum Change {
+    Update,
+    Delete,
+}
+
+impl History {
+    /// history of the connectors of a cluster, kept under `~/.kofr/history/<cluster>/`
+    pub fn for_cluster(cluster: &str) -> Option<Self> {
+        let mut dir = home_dir()?;
+        dir.push(".kofr/history");
+        Some(Self::new(dir.join(escape(cluster))))
+    }
+
+    pub fn new(dir: impl Into<PathBuf>) -> Self {
+        Self { dir: dir.into() }
+    }
+
+    /// snapshots the config of a connector about to be changed, returns the new revision or
+    /// `None` when the config is the same as the latest snapshot's
+    pub fn record(
+        &self,
+        connector: &str,
+        change: Change,
+        config: ConnectorConfig,
+    ) -> Result<Option<usize>> {
+        let snapshots = self.snapshots(connector)?;
+        if snapshots.last().is_some_and(|last| last.config == config) {
+            return Ok(None);
+        }
+
+        let dir = self.connector_dir(connector);
+        create_private_dir(&dir).map_err(|err| {
+            KofrError::Io(format!(
+                "could not create directory \"{}\": {err}",
+                dir.display()
+            ))
+        })?;
+        let snapshot = Snapshot {
+            revision: snapshots.last().map_or(1, |last| last.revision + 1),
+            taken_at: humantime::format_rfc3339_seconds(SystemTime::now()).to_string(),
+            change,
+            config,
+        };
+        let path = dir.join(format!("{}.json", snapshot.revision));
+        write_private(&path, &serde_json::to_string_pretty(&snapshot)?).map_err(|err| {
+            KofrError::Io(format!(
+                "could not write snapshot \"{}\": {err}",
+                path.display()
+            ))
+        })?;
+
+        let stale = (snapshots.len() + 1).saturating_sub(MAX_SNAPSHOTS);
+        for old in &snapshots[..stale] {
+            let _ = std::fs::remove_file(dir.join(format!("{}.json", old.revision)));
+        }
+        Ok(Some(snapshot.revision))
+    }
+
+    /// snapshots of a connector, oldest first
+    pub fn snapshots(&self, connector: &str) -> Result<Vec<Snapshot>> {
+        let dir = self.connector_dir(connector);
+        let entries = match std::fs::read_dir(&dir) {
+            Ok(entries) => entries,
+            Err(err) if err.kind() == std::io::ErrorKind::NotFound => return Ok(Vec::new()),
+            Err(err) => {
+                return Err(KofrError::Io(format!(
+                    "error reading directory \"{}\": {err}",
+                    dir.display()
+                )))
+            }
+        };
+
+        let mut snapshots = Vec::new();
+        for path in entries.filter_map(|entry| entry.ok().map(|e| e.path())) {
+            if path.extension().and_then(|e| e.to_str()) != Some("json") {
+                continue;
+            }
+            let content = std::fs::read_to_string(&path).map_err(|err| {
+                KofrError::Io(format!("error reading file \"{}\": {err}", path.display()))
+            })?;
+            let snapshot: Snapshot = serde_json::from_str(&content).map_err(|err| {
+                KofrError::Io(format!(
+                    "invalid snapshot in file \"{}\": {err}",
+                    path.display()
+                ))
+            })?;
+            snapshots.push(snapshot);
+        }
+        snapshots.sort_by_key(|snapshot| snapshot.revision);
+        Ok(snapshots)
+    }
+
+    /// the snapshot of a connector with the given revision, the latest one by default
+    pub fn snapshot(&self, connector: &str, revision: Option<usize>) -> Result<Snapshot> {
+        let snapshot = match revision {
+            Some(revision) => self
+                .snapshots(connector)?
+                .into_iter()
+                .find(|snapshot| snapshot.revision == revision),
+            None => self.snapshots(connector)?.pop(),
+        };
+        snapshot.ok_or_else(|| {
+            let name = match revision {
+                Some(revision) => format!("{}@{}", connector, revision),
+                None => connector.to_string(),
+            };
+            KofrError::not_found("snapshot of connector", name)
+        })
+    }
+
+    fn connector_dir(&self, connector: &str) -> PathBuf {
+        self.dir.join(escape(connector))
+    }
+}
+
+impl Display for Change {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Change::Update => write!(f, "update"),
+            Change::Delete => write!(f, "delete"),
+        }
+    }
+}
+
+/// creates a directory and its missing parents, readable only by the user on unix
+fn create_private_dir(dir: &Path) -> std::io::Result<()> {
+    let mut builder = std::fs::DirBuilder::new();
+    builder.recursive(true);
+    #[cfg(unix)]
+    {
+        use std::os::unix::fs::{DirBuilderExt, PermissionsExt};
+        builder.mode(0o700).create(dir)?;
+        // directories created by older versions kept the default permissions
+        std::fs::set_permissions(dir, std::fs::Permissions::from_mode(0o700))
+    }
+    #[cfg(not(unix))]
+    builder.create(dir)
+}
+
+/// writes a file readable only by the user on unix
+fn write_private(path: &Path, content: &str) -> std::io::Result<()> {
+    let mut options = std::fs::OpenOptions::new();
+    options.write(true).create(true).truncate(true);
+    #[cfg(unix)]
+    std::os::unix::fs::OpenOptionsExt::mode(&mut options, 0o600);
+    options.open(path)?.write_all(content.as_bytes())
+}
+
+// cluster and connector names are used as directory names
+fn escape(name: &str) -> String {
+    name.replace('%', "%25").replace('/', "%2F")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn config(tasks: &str) -> ConnectorConfig {
+        [("connector.class", "A"), ("tasks.max", tasks)]
+            .iter()
+            .map(|(k, v)| (k.to_string(), v.to_string()))
+            .collect()
+    }
+
+    #[test]
+    fn test_recording_snapshots() {
+        let dir = tempfile::tempdir().unwrap();
+        let history = History::new(dir.path());
+
+        assert_eq!(
+            history
+                .record("a/sink", Change::Update, config("1"))
+                .unwrap(),
+            Some(1)
+        );
+        assert_eq!(
+            history
+                .record("a/sink", Change::Update, config("1"))
+                .unwrap(),
+            None
+        );
+        assert_eq!(
+            history
+                .record("a/sink", Change::Delete, config("2"))
+                .unwrap(),
+            Some(2)
+        );
+
+        let snapshots = history.snapshots("a/sink").unwrap();
+        assert_eq!(snapshots.len(), 2);
+        assert_eq!(snapshots[1].change, Change::Delete);
+        assert_eq!(
+            history.snapshot("a/sink", None).unwrap().config,
+            config("2")
+        );
+        assert_eq!(
+            history.snapshot("a/sink", Some(1)).unwrap().config,
+            config("1")
+        );
+        assert!(history.snapshot("a/sink", Some(3)).is_err());
+        assert!(history.snapshots("b-sink").unwrap().is_empty());
+    }
+
+    #[test]
+    fn test_oldest_snapshots_are_pruned() {
+        let dir = tempfile::tempdir().unwrap();
+        let history = History::new(dir.path());
+
+        for tasks in 0..MAX_SNAPSHOTS + 2 {
+            history
+                .record("a-sink", Change::Update, config(&tasks.to_string()))
+                .unwrap();
+        }
+
+        let snapshots = history.snapshots("a-sink").unwrap();
+        assert_eq!(snapshots.len(), MAX_SNAPSHOTS);
+        assert_eq!(snapshots[0].revision, 3);
+        assert_eq!(snapshots.last().unwrap().revision, MAX_SNAPSHOTS + 2);
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_snapshots_are_private() {
+        use std::os::unix::fs::PermissionsExt;
+
+        let dir = tempfile::tempdir().unwrap();
+        let history = History::new(dir.path().join("dev"));
+        history
+            .record("a-sink", Change::Update, config("1"))
+            .unwrap();
+
+        let mode = |path: PathBuf| std::fs::metadata(path).unwrap().permissions().mode() & 0o777;
+        assert_eq!(mode(dir.path().join("dev")), 0o700);
+        assert_eq!(mode(dir.path().join("dev/a-sink")), 0o700);
+        assert_eq!(mode(dir.path().join("dev/a-sink/1.json")), 0o600);
+    }
+}
@@ -0,0 +1,47 @@
+//! A client for the [Kafka Connect REST API](https://kafka.apache.org/documentation/#connect_rest),
+//! used by the `kofr` CLI.
+//!
+//! [`HTTPClient`] wraps every endpoint of a Connect cluster and returns the serde models of the
+//! responses, like [`connect::ConnectorStatus`] or [`tasks::TaskStatus`], and a typed
+//! [`KofrError`] on failure.
+//!
+//! ```no_run
+//! use kofr::config::ClusterContext;
+//! use kofr::HTTPClient;
+//!
+//! # fn main() -> kofr::error::Result<()> {
+//! let cluster = ClusterContext {
+//!     name: "dev".to_string(),
+//!     hosts: vec!["http://localhost:8083".to_string()],
+//!     ..Default::default()
+//! };
+//! let client = HTTPClient::for_cluster(&cluster)?;
+//! for name in client.list_connectors()? {
+//!     let status = client.get_connector_status(&name)?;
+//!     println!("{}: {}", name, status.connector_state.state);
+//! }
+//! # Ok(())
+//! # }
+//! ```
+//!
+//! With the `async` feature, [`async_client::AsyncHTTPClient`] offers the same endpoints as
+//! non-blocking methods for tokio services.
+
+#[cfg(feature = "async")]
+pub mod async_client;
+pub mod cluster;
+pub mod config;
+pub mod connect;
+pub mod connector_plugins;
+pub mod error;
+pub mod history;
+pub mod loggers;
+pub mod offsets;
+pub mod tasks;
+mod tls;
+pub mod topics;
+
+#[cfg(feature = "async")]
+pub use async_client::AsyncHTTPClient;
+pub use connect::{HTTPClient, HTTPClientConfig};
+pub use error::KofrError;
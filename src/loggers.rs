@@ -0,0 +1,33 @@
+use std::collections::BTreeMap;
+use std::fmt::Display;
+
+use serde::{Deserialize, Serialize};
+
+/// Map of logger names to their levels as returned by `GET /admin/loggers`
+pub type Loggers = BTreeMap<String, LoggerLevel>;
+
+#[derive(Debug, Serialize, Deserialize, PartialEq)]
+pub struct LoggerLevel {
+    pub level: String,
+    /// milliseconds since epoch of the last level change, null if it was never modified
+    #[serde(default)]
+    pub last_modified: Option<i64>,
+}
+
+/// Whether a level change applies to the contacted worker only or to every worker in the cluster
+#[derive(Debug, Clone, Copy, PartialEq, Default)]
+#[cfg_attr(feature = "cli", derive(clap::ValueEnum))]
+pub enum Scope {
+    #[default]
+    Worker,
+    Cluster,
+}
+
+impl Display for Scope {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        match self {
+            Self::Worker => write!(f, "worker"),
+            Self::Cluster => write!(f, "cluster"),
+        }
+    }
+}
@@ -1,22 +1,28 @@
 mod cli;
-mod cluster;
-mod config;
-mod connect;
-mod connector_plugins;
-mod tasks;
-mod topics;
+mod diff;
+mod manifest;
+mod output;
+mod parallel;
+mod patch;
+mod watch;
 
-use std::{path::PathBuf, time::Duration};
+use std::path::PathBuf;
 
 use anyhow::{Context, Result};
 use clap::Parser;
 use home::home_dir;
-use ureq::Agent;
 
 use cli::*;
-use connect::HTTPClient;
+use kofr::{config, connect, error, HTTPClient};
 
-fn main() -> Result<()> {
+fn main() {
+    if let Err(err) = run() {
+        eprintln!("Error: {err:?}");
+        std::process::exit(error::exit_code(&err));
+    }
+}
+
+fn run() -> Result<()> {
     let mut cluster_config = config::Config::new()?;
     let cli = Cli::parse();
     let mut default_config_path = home_dir().context("could not get user's home dir")?;
@@ -31,74 +37,116 @@ fn main() -> Result<()> {
     match &cli.command {
         Action::ConfigAction(config_command) => match &config_command {
             ConfigAction::UseCluster(use_cluster) => {
-                use_cluster.run(&mut cluster_config)?;
+                use_cluster.run(&mut cluster_config, cli.output)?;
                 std::process::exit(exitcode::OK);
             }
             ConfigAction::CurrentContext => {
-                let current_context = cluster_config.current_context()?;
-                println!("{}", current_context.name);
+                current_context(&cluster_config, cli.output)?;
                 std::process::exit(exitcode::OK);
             }
             ConfigAction::GetClusters => {
-                for cluster in &cluster_config.clusters {
-                    println!("{}", cluster.name);
-                }
+                get_clusters(&cluster_config, cli.output)?;
                 std::process::exit(exitcode::OK);
             }
             ConfigAction::AddCluster(add_cluster) => {
-                add_cluster.run(&mut cluster_config)?;
+                add_cluster.run(&mut cluster_config, cli.output)?;
                 std::process::exit(exitcode::OK);
             }
             ConfigAction::RemoveCluster(remove) => {
-                remove.run(&mut cluster_config)?;
+                remove.run(&mut cluster_config, cli.output)?;
                 std::process::exit(exitcode::OK);
             }
         },
         Action::Cluster(status) => {
-            status.run(&cluster_config)?;
+            status.run(&cluster_config, cli.output)?;
+            std::process::exit(exitcode::OK);
+        }
+        Action::ConnectorAction(ConnectorAction::Copy(copy)) => {
+            copy.run(&cluster_config, cli.no_retry, cli.output)?;
             std::process::exit(exitcode::OK);
         }
         _ => (),
     }
 
-    let uri = &cluster_config.current_context()?.available_host()?;
-
-    let agent: Agent = ureq::AgentBuilder::new()
-        .timeout_read(Duration::from_secs(5))
-        .timeout_write(Duration::from_secs(5))
-        .build();
+    let current_context = cluster_config.current_context()?;
+    let pinned_host = match &cli.command {
+        Action::Logger(logger) => logger.host(),
+        _ => None,
+    };
+    let agent = connect::cluster_agent(current_context)?;
+    let uri = &match pinned_host {
+        Some(host) => current_context.host(host)?,
+        None => current_context.healthy_host(&agent)?,
+    };
 
     let client = HTTPClient::from_config(connect::HTTPClientConfig {
-        http_agent: (agent),
+        http_agent: agent,
         connect_uri: (uri.to_owned()),
-    });
+    })
+    .with_retry(retry_policy(current_context, cli.no_retry))
+    .with_retry_callback(report_retry);
+    let history = kofr::history::History::for_cluster(&current_context.name);
+    let client = client.with_history(history.clone());
+    // commands pinned to a host must not silently talk to another one
+    let client = match pinned_host {
+        Some(_) => client,
+        None => client.with_failover(current_context).with_host_cache(),
+    };
 
     match cli.command {
-        Action::List(list) => list.run(client)?,
+        Action::List(list) => list.run(client, cli.output)?,
+        Action::Apply(apply) => apply.run(client, cli.output)?,
+        Action::Export(export) => export.run(client, cli.output)?,
+        Action::Diff(diff) => {
+            let code = diff.run(client, cli.output)?;
+            std::process::exit(code);
+        }
         Action::ConnectorAction(connector_command) => match connector_command {
-            ConnectorAction::Create(create) => create.run(client)?,
-            ConnectorAction::Describe(describe) => describe.run(client)?,
-            ConnectorAction::Edit(edit) => edit.run(client)?,
-            ConnectorAction::Status(status) => status.run(client)?,
-            ConnectorAction::Config(config) => config.run(client)?,
-            ConnectorAction::Pause(pause) => pause.run(client)?,
-            ConnectorAction::Resume(resume) => resume.run(client)?,
-            ConnectorAction::Restart(restart) => restart.run(client)?,
-            ConnectorAction::Delete(delete) => delete.run(client)?,
-            ConnectorAction::Patch(patch) => patch.run(client)?,
+            ConnectorAction::Create(create) => create.run(client, cli.output)?,
+            ConnectorAction::Describe(describe) => describe.run(client, cli.output)?,
+            ConnectorAction::Edit(edit) => edit.run(client, cli.output)?,
+            ConnectorAction::Status(status) => status.run(client, cli.output)?,
+            ConnectorAction::Config(config) => config.run(client, cli.output)?,
+            ConnectorAction::Pause(pause) => pause.run(client, cli.output)?,
+            ConnectorAction::Resume(resume) => resume.run(client, cli.output)?,
+            ConnectorAction::Stop(stop) => stop.run(client, cli.output)?,
+            ConnectorAction::Restart(restart) => restart.run(client, cli.output)?,
+            ConnectorAction::Delete(delete) => delete.run(client, cli.output)?,
+            ConnectorAction::Patch(patch) => patch.run(client, cli.output)?,
+            ConnectorAction::Set(set) => set.run(client, cli.output)?,
+            ConnectorAction::Unset(unset) => unset.run(client, cli.output)?,
+            ConnectorAction::History(list) => list.run(history.as_ref(), cli.output)?,
+            ConnectorAction::Rollback(rollback) => {
+                rollback.run(client, history.as_ref(), cli.output)?
+            }
+            ConnectorAction::Copy(_) => (),
+            ConnectorAction::Wait(wait) => {
+                let code = wait.run(client, cli.output)?;
+                std::process::exit(code);
+            }
+            ConnectorAction::Offsets(offsets) => match offsets {
+                Offsets::Get(get) => get.run(client, cli.output)?,
+                Offsets::Set(set) => set.run(client, cli.output)?,
+                Offsets::Reset(reset) => reset.run(client, cli.output)?,
+            },
         },
         Action::Task(task) => match task {
-            Task::List(list) => list.run(uri)?,
-            Task::Restart(restart) => restart.run(uri)?,
-            Task::Status(status) => status.run(uri)?,
+            Task::List(list) => list.run(client, cli.output)?,
+            Task::Restart(restart) => restart.run(client, cli.output)?,
+            Task::Status(status) => status.run(client, cli.output)?,
         },
         Action::Topic(topic) => match topic {
-            Topic::List(list) => list.run(uri)?,
-            Topic::Reset(reset) => reset.run(uri)?,
+            Topic::List(list) => list.run(client, cli.output)?,
+            Topic::Reset(reset) => reset.run(client, cli.output)?,
         },
         Action::Plugin(plugin) => match plugin {
-            Plugin::List(list) => list.run(uri)?,
-            Plugin::ValidateConfig(validate_config) => validate_config.run(uri)?,
+            Plugin::List(list) => list.run(client, cli.output)?,
+            Plugin::ValidateConfig(validate_config) => validate_config.run(client, cli.output)?,
+        },
+        Action::Logger(logger) => match logger {
+            Logger::List(list) => list.run(client, cli.output)?,
+            Logger::Get(get) => get.run(client, cli.output)?,
+            Logger::Set(set) => set.run(client, cli.output)?,
         },
         _ => (),
     }
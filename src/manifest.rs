@@ -0,0 +1,361 @@
+use std::collections::BTreeMap;
+use std::io::Read;
+use std::path::{Path, PathBuf};
+
+use anyhow::{Context, Result};
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+
+use kofr::connect::{ConnectorConfig, ConnectorName, ConnectorStatus, CreateConnector};
+use kofr::error::KofrError;
+
+/// Serialization format of connector definitions
+#[derive(clap::ValueEnum, Debug, Clone, Copy, PartialEq, Default)]
+pub enum ManifestFormat {
+    #[default]
+    Json,
+    Yaml,
+}
+
+/// A connector definition as written by export, readable back by apply. The status snapshot is
+/// informational only and ignored when the definition is read back.
+#[derive(Debug, Serialize)]
+pub struct ExportedConnector {
+    pub name: ConnectorName,
+    pub config: BTreeMap<String, String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub status: Option<ConnectorStatus>,
+}
+
+/// Reads connector definitions from a file, every `.json`, `.yaml` or `.yml` file of a directory,
+/// or stdin when path is `-`. A single file may hold one connector definition or an array of them,
+/// YAML files may also hold a stream of documents separated by `---`.
+pub fn read_manifests(path: &Path) -> Result<Vec<CreateConnector>> {
+    if path == Path::new("-") {
+        let mut content = String::new();
+        std::io::stdin()
+            .read_to_string(&mut content)
+            .context("failed reading connectors from stdin")?;
+        return parse_manifest(&content, ManifestFormat::detect(&content))
+            .context("invalid connector definition read from stdin");
+    }
+
+    let mut files: Vec<PathBuf> = if path.is_dir() {
+        std::fs::read_dir(path)
+            .with_context(|| format!("error reading directory \"{}\"", path.to_string_lossy()))?
+            .filter_map(|entry| entry.ok().map(|e| e.path()))
+            .filter(|p| p.is_file() && is_manifest(p))
+            .collect()
+    } else {
+        vec![path.to_path_buf()]
+    };
+    files.sort();
+
+    let mut connectors = Vec::new();
+    for file in files {
+        let content = std::fs::read_to_string(&file)
+            .with_context(|| format!("error reading file \"{}\"", file.to_string_lossy()))?;
+        let format = ManifestFormat::from_path(&file).unwrap_or(ManifestFormat::detect(&content));
+        let mut parsed = parse_manifest(&content, format).with_context(|| {
+            format!(
+                "invalid connector definition in file \"{}\"",
+                file.to_string_lossy()
+            )
+        })?;
+        connectors.append(&mut parsed);
+    }
+    Ok(connectors)
+}
+
+/// The config a connector is expected to have on the cluster once created from this definition,
+/// connect always adds the connector's name to its config.
+pub fn desired_config(connector: &CreateConnector) -> ConnectorConfig {
+    let mut config = connector.config.clone();
+    config
+        .entry("name".to_string())
+        .or_insert_with(|| connector.name.0.clone());
+    config
+}
+
+/// Replaces the values of config keys that usually hold credentials
+pub fn redact_secrets(config: &mut ConnectorConfig) {
+    for (key, value) in config.iter_mut() {
+        let key = key.to_lowercase();
+        if SECRET_KEYS.iter().any(|secret| key.contains(secret)) && !is_config_provider(value) {
+            *value = REDACTED.to_string();
+        }
+    }
+}
+
+/// Fails on definitions still holding values replaced by `redact_secrets`, creating or applying
+/// them would overwrite the real credentials with the placeholder
+pub fn ensure_not_redacted(connectors: &[CreateConnector]) -> Result<()> {
+    let redacted: Vec<String> = connectors
+        .iter()
+        .flat_map(|connector| {
+            let mut keys: Vec<&String> = connector
+                .config
+                .iter()
+                .filter(|(_, value)| value.as_str() == REDACTED)
+                .map(|(key, _)| key)
+                .collect();
+            keys.sort();
+            keys.into_iter()
+                .map(move |key| format!("connector/{}: {}", connector.name.0, key))
+        })
+        .collect();
+    if redacted.is_empty() {
+        return Ok(());
+    }
+    Err(KofrError::Validation(format!(
+        "connector definitions hold redacted values, set the real ones or pass --allow-redacted:\n{}",
+        redacted.join("\n")
+    ))
+    .into())
+}
+
+impl ExportedConnector {
+    pub fn render(&self, format: ManifestFormat) -> Result<String> {
+        match format {
+            ManifestFormat::Json => {
+                let mut json = serde_json::to_string_pretty(self)?;
+                json.push('\n');
+                Ok(json)
+            }
+            ManifestFormat::Yaml => Ok(serde_yaml::to_string(self)?),
+        }
+    }
+}
+
+impl ManifestFormat {
+    pub fn from_path(path: &Path) -> Option<Self> {
+        match path.extension().and_then(|e| e.to_str()) {
+            Some("json") => Some(Self::Json),
+            Some("yaml") | Some("yml") => Some(Self::Yaml),
+            _ => None,
+        }
+    }
+
+    pub fn extension(&self) -> &'static str {
+        match self {
+            Self::Json => "json",
+            Self::Yaml => "yaml",
+        }
+    }
+
+    fn detect(content: &str) -> Self {
+        match content.trim_start().chars().next() {
+            Some('{') | Some('[') => Self::Json,
+            _ => Self::Yaml,
+        }
+    }
+}
+
+const REDACTED: &str = "<redacted>";
+const SECRET_KEYS: [&str; 6] = [
+    "password",
+    "secret",
+    "token",
+    "credentials",
+    "api.key",
+    "jaas.config",
+];
+
+// values resolved by a config provider, e.g. ${file:/secrets.properties:password}, hold no secret
+fn is_config_provider(value: &str) -> bool {
+    value.starts_with("${") && value.ends_with('}')
+}
+
+fn parse_manifest(content: &str, format: ManifestFormat) -> Result<Vec<CreateConnector>> {
+    match format {
+        ManifestFormat::Json => parse_definitions(serde_json::from_str(content)?),
+        ManifestFormat::Yaml => {
+            let mut connectors = Vec::new();
+            for document in serde_yaml::Deserializer::from_str(content) {
+                let value = Value::deserialize(document)?;
+                if !value.is_null() {
+                    connectors.append(&mut parse_definitions(value)?);
+                }
+            }
+            Ok(connectors)
+        }
+    }
+}
+
+/// The connector definitions of a document holding one definition or an array of them
+fn parse_definitions(value: Value) -> Result<Vec<CreateConnector>> {
+    let mut definitions = match value {
+        Value::Array(definitions) => definitions,
+        definition => vec![definition],
+    };
+    for definition in definitions.iter_mut() {
+        if let Some(config) = definition.get_mut("config") {
+            stringify_values(config);
+        }
+    }
+    definitions
+        .into_iter()
+        .map(|definition| Ok(serde_json::from_value(definition)?))
+        .collect()
+}
+
+/// Renders a connector config for editing, keys sorted
+pub fn render_config(config: &ConnectorConfig, format: ManifestFormat) -> Result<String> {
+    let sorted: BTreeMap<&String, &String> = config.iter().collect();
+    match format {
+        ManifestFormat::Json => Ok(serde_json::to_string_pretty(&sorted)?),
+        ManifestFormat::Yaml => Ok(serde_yaml::to_string(&sorted)?),
+    }
+}
+
+/// Reads a connector config written in the given format
+pub fn parse_config(content: &str, format: ManifestFormat) -> Result<ConnectorConfig> {
+    let mut config: Value = match format {
+        ManifestFormat::Json => serde_json::from_str(content)?,
+        ManifestFormat::Yaml => serde_yaml::from_str(content)?,
+    };
+    stringify_values(&mut config);
+    Ok(serde_json::from_value(config)?)
+}
+
+// connect configs only hold strings, numbers and booleans written without quotes keep their text
+fn stringify_values(config: &mut Value) {
+    if let Some(config) = config.as_object_mut() {
+        for value in config.values_mut() {
+            if value.is_number() || value.is_boolean() {
+                *value = Value::String(value.to_string());
+            }
+        }
+    }
+}
+
+fn is_manifest(path: &Path) -> bool {
+    ManifestFormat::from_path(path).is_some()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_reading_manifests_from_directory() {
+        let dir = tempfile::tempdir().unwrap();
+        std::fs::write(
+            dir.path().join("a.json"),
+            r#"{"name": "a-sink", "config": {"connector.class": "A"}}"#,
+        )
+        .unwrap();
+        std::fs::write(
+            dir.path().join("b.json"),
+            r#"[
+                {"name": "b-sink", "config": {"connector.class": "B"}},
+                {"name": "c-source", "config": {"connector.class": "C"}}
+            ]"#,
+        )
+        .unwrap();
+        std::fs::write(dir.path().join("notes.txt"), "not a connector").unwrap();
+
+        let connectors = read_manifests(dir.path()).unwrap();
+        let names: Vec<&str> = connectors.iter().map(|c| c.name.0.as_str()).collect();
+        assert_eq!(names, vec!["a-sink", "b-sink", "c-source"]);
+    }
+
+    #[test]
+    fn test_reading_yaml_manifest() {
+        let dir = tempfile::tempdir().unwrap();
+        let file = dir.path().join("a.yaml");
+        std::fs::write(
+            &file,
+            r#"
+name: a-sink
+config:
+  connector.class: A
+  tasks.max: "2"
+"#,
+        )
+        .unwrap();
+
+        let connectors = read_manifests(&file).unwrap();
+        assert_eq!(connectors[0].name.0, "a-sink");
+        assert_eq!(
+            connectors[0].config.get("tasks.max"),
+            Some(&"2".to_string())
+        );
+    }
+
+    #[test]
+    fn test_reading_multi_document_yaml() {
+        let content = r#"
+name: a-sink
+config:
+  connector.class: A
+  tasks.max: 2
+---
+- name: b-sink
+  config:
+    connector.class: B
+    errors.tolerance: all
+    errors.log.enable: true
+---
+"#;
+
+        let connectors = parse_manifest(content, ManifestFormat::detect(content)).unwrap();
+        let names: Vec<&str> = connectors.iter().map(|c| c.name.0.as_str()).collect();
+        assert_eq!(names, vec!["a-sink", "b-sink"]);
+        assert_eq!(connectors[0].config["tasks.max"], "2");
+        assert_eq!(connectors[1].config["errors.log.enable"], "true");
+    }
+
+    #[test]
+    fn test_config_round_trip() {
+        let config: ConnectorConfig = [("tasks.max", "1"), ("topics", "users")]
+            .iter()
+            .map(|(k, v)| (k.to_string(), v.to_string()))
+            .collect();
+
+        for format in [ManifestFormat::Json, ManifestFormat::Yaml] {
+            let rendered = render_config(&config, format).unwrap();
+            assert_eq!(parse_config(&rendered, format).unwrap(), config);
+        }
+        assert_eq!(
+            parse_config("tasks.max: 4\n", ManifestFormat::Yaml).unwrap()["tasks.max"],
+            "4"
+        );
+    }
+
+    #[test]
+    fn test_redacting_secrets() {
+        let mut config: ConnectorConfig = [
+            ("connection.password", "hunter2"),
+            (
+                "key.converter",
+                "org.apache.kafka.connect.json.JsonConverter",
+            ),
+            ("aws.secret.access.key", "${file:/secrets.properties:aws}"),
+        ]
+        .iter()
+        .map(|(k, v)| (k.to_string(), v.to_string()))
+        .collect();
+
+        redact_secrets(&mut config);
+        assert_eq!(config["connection.password"], REDACTED);
+        assert_eq!(
+            config["key.converter"],
+            "org.apache.kafka.connect.json.JsonConverter"
+        );
+        assert_eq!(
+            config["aws.secret.access.key"],
+            "${file:/secrets.properties:aws}"
+        );
+    }
+
+    #[test]
+    fn test_desired_config_includes_name() {
+        let connector: CreateConnector =
+            serde_json::from_str(r#"{"name": "a-sink", "config": {"connector.class": "A"}}"#)
+                .unwrap();
+
+        let config = desired_config(&connector);
+        assert_eq!(config.get("name"), Some(&"a-sink".to_string()));
+    }
+}
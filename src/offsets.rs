@@ -0,0 +1,148 @@
+use serde::{Deserialize, Serialize};
+
+#[derive(Debug, Serialize, Deserialize, PartialEq)]
+pub struct ConnectorOffsets {
+    pub offsets: Vec<PartitionOffset>,
+}
+
+/// A single partition/offset pair as returned by `GET /connectors/<name>/offsets`.
+/// Sink connectors report kafka topic partitions while source connectors report
+/// arbitrary, connector defined maps.
+#[derive(Debug, Serialize, Deserialize, PartialEq)]
+#[serde(untagged)]
+pub enum PartitionOffset {
+    Sink(SinkOffset),
+    Source(SourceOffset),
+}
+
+#[derive(Debug, Serialize, Deserialize, PartialEq)]
+pub struct SinkOffset {
+    pub partition: SinkPartition,
+    /// `None` resets the partition's offset when altering offsets, as does leaving it out
+    #[serde(default)]
+    pub offset: Option<SinkOffsetValue>,
+}
+
+#[derive(Debug, Serialize, Deserialize, PartialEq)]
+#[serde(deny_unknown_fields)]
+pub struct SinkPartition {
+    pub kafka_topic: String,
+    pub kafka_partition: i32,
+}
+
+#[derive(Debug, Serialize, Deserialize, PartialEq)]
+#[serde(deny_unknown_fields)]
+pub struct SinkOffsetValue {
+    pub kafka_offset: i64,
+}
+
+#[derive(Debug, Serialize, Deserialize, PartialEq)]
+pub struct SourceOffset {
+    pub partition: serde_json::Map<String, serde_json::Value>,
+    /// `None` resets the partition's offset when altering offsets, as does leaving it out
+    #[serde(default)]
+    pub offset: Option<serde_json::Map<String, serde_json::Value>>,
+}
+
+/// Message returned by the connect cluster after altering or resetting offsets
+#[derive(Debug, Serialize, Deserialize)]
+pub struct OffsetsResponse {
+    pub message: String,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_deserializing_sink_offsets() {
+        let offsets = r#"
+        {
+            "offsets": [
+                {
+                    "partition": {"kafka_topic": "orders", "kafka_partition": 2},
+                    "offset": {"kafka_offset": 4}
+                }
+            ]
+        }"#;
+
+        let offsets: ConnectorOffsets = serde_json::from_str(offsets).unwrap();
+        assert_eq!(
+            offsets.offsets[0],
+            PartitionOffset::Sink(SinkOffset {
+                partition: SinkPartition {
+                    kafka_topic: "orders".to_string(),
+                    kafka_partition: 2,
+                },
+                offset: Some(SinkOffsetValue { kafka_offset: 4 }),
+            })
+        );
+    }
+
+    #[test]
+    fn test_deserializing_source_offsets() {
+        let offsets = r#"
+        {
+            "offsets": [
+                {
+                    "partition": {"filename": "/data/orders.txt"},
+                    "offset": {"position": 30}
+                },
+                {
+                    "partition": {"filename": "/data/users.txt"},
+                    "offset": null
+                }
+            ]
+        }"#;
+
+        let offsets: ConnectorOffsets = serde_json::from_str(offsets).unwrap();
+        assert!(matches!(offsets.offsets[0], PartitionOffset::Source(_)));
+        assert!(matches!(
+            &offsets.offsets[1],
+            PartitionOffset::Source(SourceOffset { offset: None, .. })
+        ));
+    }
+
+    #[test]
+    fn test_reset_partition_serializes_null_offset() {
+        let offset = PartitionOffset::Sink(SinkOffset {
+            partition: SinkPartition {
+                kafka_topic: "orders".to_string(),
+                kafka_partition: 0,
+            },
+            offset: None,
+        });
+
+        let offset = serde_json::to_value(&offset).unwrap();
+        assert_eq!(offset["offset"], serde_json::Value::Null);
+    }
+
+    #[test]
+    fn test_partition_without_offset_is_reset() {
+        let offsets = r#"
+        {
+            "offsets": [
+                {"partition": {"kafka_topic": "orders", "kafka_partition": 1}},
+                {"partition": {"filename": "/data/users.txt"}}
+            ]
+        }"#;
+
+        let offsets: ConnectorOffsets = serde_json::from_str(offsets).unwrap();
+        assert!(matches!(
+            &offsets.offsets[0],
+            PartitionOffset::Sink(SinkOffset { offset: None, .. })
+        ));
+        assert!(matches!(
+            &offsets.offsets[1],
+            PartitionOffset::Source(SourceOffset { offset: None, .. })
+        ));
+
+        let round_trip = serde_json::to_string(&offsets).unwrap();
+        let round_trip: ConnectorOffsets = serde_json::from_str(&round_trip).unwrap();
+        assert_eq!(round_trip, offsets);
+        assert_eq!(
+            serde_json::to_value(&offsets).unwrap()["offsets"][0]["offset"],
+            serde_json::Value::Null
+        );
+    }
+}
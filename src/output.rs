@@ -0,0 +1,410 @@
+use std::collections::BTreeMap;
+
+use anyhow::Result;
+use kofr::cluster::UriStatus;
+use kofr::connect::{ConnectorConfig, VerboseConnector};
+use kofr::connector_plugins::ConnectorPlugin;
+use kofr::history::{Change, Snapshot};
+use kofr::loggers::{LoggerLevel, Loggers};
+use kofr::offsets::{ConnectorOffsets, PartitionOffset};
+use kofr::tasks::TaskStatus;
+use kofr::topics::Topic;
+use serde::Serialize;
+use tabled::settings::{locator::ByColumnName, Disable, Style};
+use tabled::Table;
+
+use crate::diff::ConfigDiff;
+
+/// Output format of commands, selected with the global `-o/--output` flag
+#[derive(clap::ValueEnum, Debug, Clone, Copy, PartialEq)]
+pub enum Output {
+    /// human readable table
+    Table,
+    /// table with additional columns
+    Wide,
+    /// pretty printed json
+    Json,
+    /// yaml
+    Yaml,
+    /// names only, one per line, for piping into other commands
+    Name,
+}
+
+/// A command result that can be printed in every output format
+pub trait Printable: Serialize {
+    /// table shown by the table output, `wide` adds extra columns where available
+    fn table(&self, wide: bool) -> String;
+    /// names printed one per line by the name output
+    fn names(&self) -> Vec<String>;
+}
+
+pub fn print<T: Printable>(value: &T, output: Output) -> Result<()> {
+    match output {
+        Output::Table => println!("{}", value.table(false)),
+        Output::Wide => println!("{}", value.table(true)),
+        Output::Json | Output::Yaml => serialize(value, output)?,
+        Output::Name => {
+            for name in value.names() {
+                println!("{}", name);
+            }
+        }
+    }
+    Ok(())
+}
+
+/// Prints a value with no tabular form, table outputs fall back to json and the name output
+/// prints only the given name
+pub fn print_document<T: Serialize>(value: &T, name: &str, output: Output) -> Result<()> {
+    match output {
+        Output::Name => println!("{}", name),
+        _ => serialize(value, output)?,
+    }
+    Ok(())
+}
+
+/// The outcome of a command on a resource, printed as its message by table outputs and as a
+/// document by json and yaml ones
+#[derive(Serialize, Debug)]
+pub struct Outcome {
+    pub name: String,
+    pub message: String,
+    /// changed keys of a connector config, table outputs print them below the message
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub changes: Option<ConfigDiff>,
+}
+
+impl Outcome {
+    pub fn new(name: impl Into<String>, message: impl Into<String>) -> Self {
+        Self {
+            name: name.into(),
+            message: message.into(),
+            changes: None,
+        }
+    }
+
+    pub fn with_changes(self, changes: ConfigDiff) -> Self {
+        Self {
+            changes: Some(changes),
+            ..self
+        }
+    }
+}
+
+/// Prints the outcome message of a command, or only the affected name for the name output
+pub fn print_message(message: &str, name: &str, output: Option<Output>) -> Result<()> {
+    print_outcome(&Outcome::new(name, message), output)
+}
+
+pub fn print_outcome(outcome: &Outcome, output: Option<Output>) -> Result<()> {
+    match output {
+        Some(format @ (Output::Json | Output::Yaml)) => serialize(outcome, format),
+        _ => print_outcomes(std::slice::from_ref(outcome), output),
+    }
+}
+
+/// Prints the outcomes of a command run on many resources, json and yaml outputs print them as
+/// a single list
+pub fn print_outcomes(outcomes: &[Outcome], output: Option<Output>) -> Result<()> {
+    print!(
+        "{}",
+        render_outcomes(outcomes, output, crate::diff::use_color())?
+    );
+    Ok(())
+}
+
+fn render_outcomes(outcomes: &[Outcome], output: Option<Output>, color: bool) -> Result<String> {
+    let mut out = String::new();
+    match output {
+        Some(format @ (Output::Json | Output::Yaml)) => return render(&outcomes, format),
+        Some(Output::Name) => {
+            for outcome in outcomes {
+                out.push_str(&format!("{}\n", outcome.name));
+            }
+        }
+        _ => {
+            for outcome in outcomes {
+                out.push_str(&format!("{}\n", outcome.message));
+                if let Some(changes) = &outcome.changes {
+                    out.push_str(&changes.key_level(color));
+                }
+            }
+        }
+    }
+    Ok(out)
+}
+
+/// Renders rows as a borderless table, the style used by every kofr table
+pub fn table<T: tabled::Tabled>(rows: impl IntoIterator<Item = T>) -> Table {
+    let mut table = Table::new(rows);
+    table.with(Style::blank());
+    table
+}
+
+fn serialize<T: Serialize>(value: &T, output: Output) -> Result<()> {
+    print!("{}", render(value, output)?);
+    Ok(())
+}
+
+fn render<T: Serialize>(value: &T, output: Output) -> Result<String> {
+    match output {
+        Output::Yaml => Ok(serde_yaml::to_string(value)?),
+        _ => Ok(format!("{}\n", serde_json::to_string_pretty(value)?)),
+    }
+}
+
+impl Printable for Vec<UriStatus> {
+    fn table(&self, wide: bool) -> String {
+        let mut table = table(self);
+        if !wide {
+            table.with(Disable::column(ByColumnName::new("REASON")));
+        }
+        table.to_string()
+    }
+
+    fn names(&self) -> Vec<String> {
+        self.iter().map(|h| h.uri.to_string()).collect()
+    }
+}
+
+impl Printable for Vec<VerboseConnector> {
+    fn table(&self, wide: bool) -> String {
+        let mut table = table(self);
+        if !wide {
+            table.with(Disable::column(ByColumnName::new("CLASS")));
+        }
+        table.to_string()
+    }
+
+    fn names(&self) -> Vec<String> {
+        self.iter().map(|c| c.name.to_string()).collect()
+    }
+}
+
+impl Printable for ConnectorConfig {
+    fn table(&self, _wide: bool) -> String {
+        let sorted: BTreeMap<&String, &String> = self.iter().collect();
+        let rows = sorted.into_iter().map(|(key, value)| ConfigRow {
+            key: key.to_string(),
+            value: value.to_string(),
+        });
+        table(rows).to_string()
+    }
+
+    fn names(&self) -> Vec<String> {
+        let mut keys: Vec<String> = self.keys().cloned().collect();
+        keys.sort();
+        keys
+    }
+}
+
+#[derive(tabled::Tabled)]
+struct ConfigRow {
+    #[tabled(rename = "KEY")]
+    key: String,
+    #[tabled(rename = "VALUE")]
+    value: String,
+}
+
+impl Printable for Vec<ConnectorPlugin> {
+    fn table(&self, _wide: bool) -> String {
+        table(self).to_string()
+    }
+
+    fn names(&self) -> Vec<String> {
+        self.iter().map(|p| p.class.to_string()).collect()
+    }
+}
+
+impl Printable for Vec<Snapshot> {
+    fn table(&self, _wide: bool) -> String {
+        let rows = self.iter().map(|snapshot| SnapshotRow {
+            revision: snapshot.revision,
+            taken_at: snapshot.taken_at.clone(),
+            change: snapshot.change,
+        });
+        table(rows).to_string()
+    }
+
+    fn names(&self) -> Vec<String> {
+        self.iter().map(|s| s.revision.to_string()).collect()
+    }
+}
+
+#[derive(tabled::Tabled)]
+struct SnapshotRow {
+    #[tabled(rename = "REVISION")]
+    revision: usize,
+    #[tabled(rename = "TAKEN_AT")]
+    taken_at: String,
+    #[tabled(rename = "CHANGE")]
+    change: Change,
+}
+
+impl Printable for Loggers {
+    fn table(&self, _wide: bool) -> String {
+        let rows = self.iter().map(|(name, level)| LoggerRow::new(name, level));
+        table(rows).to_string()
+    }
+
+    fn names(&self) -> Vec<String> {
+        self.keys().cloned().collect()
+    }
+}
+
+#[derive(tabled::Tabled, Debug)]
+struct LoggerRow {
+    #[tabled(rename = "LOGGER")]
+    name: String,
+    #[tabled(rename = "LEVEL")]
+    level: String,
+    #[tabled(rename = "LAST_MODIFIED")]
+    last_modified: String,
+}
+
+impl LoggerRow {
+    fn new(name: &str, level: &LoggerLevel) -> Self {
+        Self {
+            name: name.to_string(),
+            level: level.level.clone(),
+            last_modified: match level.last_modified {
+                Some(t) => t.to_string(),
+                None => "-".to_string(),
+            },
+        }
+    }
+}
+
+impl Printable for ConnectorOffsets {
+    fn table(&self, _wide: bool) -> String {
+        table(self.offsets.iter().map(OffsetRow::from)).to_string()
+    }
+
+    fn names(&self) -> Vec<String> {
+        self.offsets
+            .iter()
+            .map(|o| OffsetRow::from(o).partition)
+            .collect()
+    }
+}
+
+#[derive(tabled::Tabled, Debug)]
+struct OffsetRow {
+    #[tabled(rename = "PARTITION")]
+    partition: String,
+    #[tabled(rename = "OFFSET")]
+    offset: String,
+}
+
+impl From<&PartitionOffset> for OffsetRow {
+    fn from(offset: &PartitionOffset) -> Self {
+        match offset {
+            PartitionOffset::Sink(sink) => Self {
+                partition: format!(
+                    "{}-{}",
+                    sink.partition.kafka_topic, sink.partition.kafka_partition
+                ),
+                offset: match &sink.offset {
+                    Some(o) => o.kafka_offset.to_string(),
+                    None => "-".to_string(),
+                },
+            },
+            PartitionOffset::Source(source) => Self {
+                partition: serde_json::Value::Object(source.partition.clone()).to_string(),
+                offset: match &source.offset {
+                    Some(o) => serde_json::Value::Object(o.clone()).to_string(),
+                    None => "-".to_string(),
+                },
+            },
+        }
+    }
+}
+
+impl Printable for Vec<TaskStatus> {
+    fn table(&self, _wide: bool) -> String {
+        table(self).to_string()
+    }
+
+    fn names(&self) -> Vec<String> {
+        self.iter().map(|t| t.id.to_string()).collect()
+    }
+}
+
+impl Printable for Topic {
+    fn table(&self, _wide: bool) -> String {
+        let rows = self.topics.iter().flat_map(|(connector, list)| {
+            list.topics.iter().map(|topic| TopicRow {
+                connector: connector.to_string(),
+                topic: topic.to_string(),
+            })
+        });
+        table(rows).to_string()
+    }
+
+    fn names(&self) -> Vec<String> {
+        self.topics
+            .values()
+            .flat_map(|list| list.topics.clone())
+            .collect()
+    }
+}
+
+#[derive(tabled::Tabled)]
+struct TopicRow {
+    #[tabled(rename = "CONNECTOR")]
+    connector: String,
+    #[tabled(rename = "TOPIC")]
+    topic: String,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    fn outcomes() -> Vec<Outcome> {
+        let old = ConnectorConfig::new();
+        let new = [("tasks.max".to_string(), "1".to_string())].into();
+        vec![
+            Outcome::new("a-sink", "connector/a-sink unchanged"),
+            Outcome::new("b-sink", "connector/b-sink created")
+                .with_changes(ConfigDiff::new(&old, &new)),
+        ]
+    }
+
+    #[test]
+    fn test_outcomes_in_every_output() {
+        let outcomes = outcomes();
+        let render = |output| render_outcomes(&outcomes, output, false).unwrap();
+
+        assert_eq!(
+            render(None),
+            "connector/a-sink unchanged\nconnector/b-sink created\n+ tasks.max: 1\n"
+        );
+        assert_eq!(render(Some(Output::Name)), "a-sink\nb-sink\n");
+
+        let expected = json!([
+            {"name": "a-sink", "message": "connector/a-sink unchanged"},
+            {
+                "name": "b-sink",
+                "message": "connector/b-sink created",
+                "changes": [{"change": "added", "key": "tasks.max", "value": "1"}]
+            }
+        ]);
+        let json: serde_json::Value = serde_json::from_str(&render(Some(Output::Json))).unwrap();
+        assert_eq!(json, expected);
+        let yaml: serde_json::Value = serde_yaml::from_str(&render(Some(Output::Yaml))).unwrap();
+        assert_eq!(yaml, expected);
+    }
+
+    #[test]
+    fn test_messages_are_documents() {
+        let message = Outcome::new("a-sink", "connector: 'a-sink' paused");
+
+        let json: serde_json::Value =
+            serde_json::from_str(&render(&message, Output::Json).unwrap()).unwrap();
+        assert_eq!(
+            json,
+            json!({"name": "a-sink", "message": "connector: 'a-sink' paused"})
+        );
+    }
+}
@@ -0,0 +1,91 @@
+use serde_json::Value;
+
+use kofr::connect::ConnectorConfig;
+use kofr::error::{KofrError, Result};
+
+/// Applies a patch to a connector config. An object is read as an RFC 7386 merge patch, where
+/// `null` removes a key, an array as RFC 6902 JSON Patch operations. Numbers and booleans of the
+/// patched config are kept as strings, like Connect does.
+pub fn apply(config: &ConnectorConfig, patch: &str) -> Result<ConnectorConfig> {
+    let patch: Value = serde_json::from_str(patch)
+        .map_err(|err| KofrError::Validation(format!("invalid patch format: {err}")))?;
+    let mut document = serde_json::to_value(config)?;
+    match patch {
+        Value::Object(_) => json_patch::merge(&mut document, &patch),
+        Value::Array(_) => {
+            let operations: json_patch::Patch = serde_json::from_value(patch).map_err(|err| {
+                KofrError::Validation(format!("invalid JSON Patch operations: {err}"))
+            })?;
+            json_patch::patch(&mut document, &operations)
+                .map_err(|err| KofrError::Validation(format!("could not apply patch: {err}")))?;
+        }
+        _ => {
+            return Err(KofrError::Validation(
+                "a patch must be an object of keys to merge or an array of JSON Patch operations"
+                    .to_string(),
+            ))
+        }
+    }
+
+    let Value::Object(patched) = document else {
+        return Err(KofrError::Validation(
+            "the patched config must be an object".to_string(),
+        ));
+    };
+    patched
+        .into_iter()
+        .map(|(key, value)| match value {
+            Value::String(value) => Ok((key, value)),
+            Value::Number(_) | Value::Bool(_) => Ok((key, value.to_string())),
+            _ => Err(KofrError::Validation(format!(
+                "config key \"{key}\" must be a string, got: {value}"
+            ))),
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn config(pairs: &[(&str, &str)]) -> ConnectorConfig {
+        pairs
+            .iter()
+            .map(|(k, v)| (k.to_string(), v.to_string()))
+            .collect()
+    }
+
+    #[test]
+    fn test_merge_patch_keeps_other_keys() {
+        let current = config(&[
+            ("tasks.max", "1"),
+            ("topics", "users"),
+            ("batch.size", "10"),
+        ]);
+
+        let patched = apply(&current, r#"{"tasks.max": 4, "batch.size": null}"#).unwrap();
+        assert_eq!(patched, config(&[("tasks.max", "4"), ("topics", "users")]));
+    }
+
+    #[test]
+    fn test_json_patch_operations() {
+        let current = config(&[("tasks.max", "1"), ("topics", "users")]);
+
+        let patched = apply(
+            &current,
+            r#"[{"op": "replace", "path": "/tasks.max", "value": "2"},
+                {"op": "add", "path": "/errors.tolerance", "value": "all"},
+                {"op": "remove", "path": "/topics"}]"#,
+        )
+        .unwrap();
+        assert_eq!(
+            patched,
+            config(&[("tasks.max", "2"), ("errors.tolerance", "all")])
+        );
+
+        let err = apply(&current, r#"[{"op": "remove", "path": "/missing"}]"#).unwrap_err();
+        assert!(matches!(err, KofrError::Validation(_)));
+        assert!(apply(&current, r#"{"topics": {"a": "b"}}"#).is_err());
+        assert!(apply(&current, "\"tasks.max\"").is_err());
+    }
+}
@@ -1,73 +1,84 @@
 use std::collections::HashMap;
 use std::fmt::Display;
+use std::str::FromStr;
 
-use anyhow::{anyhow, Context, Result};
+use anyhow::anyhow;
 use serde::{Deserialize, Serialize};
 
-use crate::connect::ConnectorName;
-
-pub fn list_tasks(host: &str, connector_name: &str) -> Result<Vec<TaskResponse>> {
-    let endpoint = valid_uri(host);
-    let endpoint = format!("{}/{}/tasks", &endpoint, connector_name);
-
-    match ureq::get(&endpoint)
-        .set("Accept", "application/json")
-        .call()
-    {
-        Ok(response) => response
-            .into_json::<Vec<TaskResponse>>()
-            .context("invalid json returned from api"),
-        Err(ureq::Error::Status(404, _)) => {
-            Err(anyhow!("connector: \"{}\" was not found", connector_name))
+use crate::connect::{ConnectorName, HTTPClient};
+use crate::error::{KofrError, Result};
+
+impl HTTPClient {
+    /// lists the tasks of a connector with their config wrapping GET request to
+    /// /connectors/<name>/tasks
+    pub fn list_tasks(&self, connector_name: &str) -> Result<Vec<TaskResponse>> {
+        match self.send(
+            |uri| {
+                self.config
+                    .http_agent
+                    .get(&format!("{}/{}/tasks", self.valid_uri(uri), connector_name))
+                    .set("Accept", "application/json")
+            },
+            None,
+        ) {
+            Ok(response) => Ok(response.into_json::<Vec<TaskResponse>>()?),
+            Err(ureq::Error::Status(404, _)) => {
+                Err(KofrError::not_found("connector", connector_name))
+            }
+            Err(err) => Err(err.into()),
         }
-        Err(ureq::Error::Status(_, r)) => Err(anyhow!("{}", r.into_string()?)),
-        Err(err) => Err(anyhow!("{}", err)),
     }
-}
-
-pub fn task_status(host: &str, connector_name: &str, task_id: usize) -> Result<TaskStatus> {
-    let endpoint = valid_uri(host);
-    let endpoint = format!("{}/{}/tasks/{}/status", &endpoint, connector_name, task_id);
-
-    match ureq::get(&endpoint)
-        .set("Accept", "application/json")
-        .call()
-    {
-        Ok(response) => response
-            .into_json::<TaskStatus>()
-            .context("invalid json returned from api"),
-        Err(ureq::Error::Status(404, _)) => Err(anyhow!(
-            "No status found for task {}-{}",
-            connector_name,
-            task_id
-        )),
-        Err(ureq::Error::Status(_, r)) => Err(anyhow!("{}", r.into_string()?)),
-        Err(err) => Err(anyhow!("{}", err)),
-    }
-}
 
-pub fn restart_task(host: &str, connector_name: &str, task_id: usize) -> Result<()> {
-    let endpoint = valid_uri(host);
-    let endpoint = format!("{}/{}/tasks/{}/restart", &endpoint, connector_name, task_id);
-    match ureq::post(&endpoint)
-        .set("Accept", "application/json")
-        .set("Content-Type", "application/json")
-        .call()
-    {
-        Ok(_) => Ok(()),
-        Err(ureq::Error::Status(404, _)) => {
-            Err(anyhow!("task: '{}/{}' not found", connector_name, task_id))
+    /// wraps GET request to /connectors/<name>/tasks/<id>/status
+    pub fn task_status(&self, connector_name: &str, task_id: usize) -> Result<TaskStatus> {
+        match self.send(
+            |uri| {
+                self.config
+                    .http_agent
+                    .get(&format!(
+                        "{}/{}/tasks/{}/status",
+                        self.valid_uri(uri),
+                        connector_name,
+                        task_id
+                    ))
+                    .set("Accept", "application/json")
+            },
+            None,
+        ) {
+            Ok(response) => Ok(response.into_json::<TaskStatus>()?),
+            Err(ureq::Error::Status(404, _)) => Err(KofrError::not_found(
+                "task",
+                format!("{}/{}", connector_name, task_id),
+            )),
+            Err(err) => Err(err.into()),
         }
-        Err(ureq::Error::Status(_, r)) => Err(anyhow!("{}", r.into_string()?)),
-        Err(err) => Err(anyhow!("{err}")),
     }
-}
 
-pub fn valid_uri(uri: &str) -> String {
-    if uri.ends_with('/') {
-        return format!("{}connectors", uri);
+    /// wraps POST request to /connectors/<name>/tasks/<id>/restart
+    pub fn restart_task(&self, connector_name: &str, task_id: usize) -> Result<()> {
+        match self.send(
+            |uri| {
+                self.config
+                    .http_agent
+                    .post(&format!(
+                        "{}/{}/tasks/{}/restart",
+                        self.valid_uri(uri),
+                        connector_name,
+                        task_id
+                    ))
+                    .set("Accept", "application/json")
+                    .set("Content-Type", "application/json")
+            },
+            None,
+        ) {
+            Ok(_) => Ok(()),
+            Err(ureq::Error::Status(404, _)) => Err(KofrError::not_found(
+                "task",
+                format!("{}/{}", connector_name, task_id),
+            )),
+            Err(err) => Err(err.into()),
+        }
     }
-    format!("{}/connectors", uri)
 }
 
 #[derive(Debug, Serialize, Deserialize, PartialEq)]
@@ -103,8 +114,8 @@ pub struct TaskID {
     pub task: usize,
 }
 
-#[derive(Debug, Serialize, Deserialize, PartialEq)]
-#[serde(rename_all = "UPPERCASE")]
+#[derive(Debug, Serialize, Deserialize, PartialEq, Clone)]
+#[serde(from = "String", into = "String")]
 pub enum TaskState {
     Running,
     Failed,
@@ -113,6 +124,8 @@ pub enum TaskState {
     Lost,
     Created,
     Dead,
+    Stopped,
+    Unknown(String),
 }
 
 impl std::str::FromStr for TaskState {
@@ -122,15 +135,29 @@ impl std::str::FromStr for TaskState {
         match input {
             "RUNNING" => Ok(TaskState::Running),
             "FAILED" => Ok(TaskState::Failed),
+            "PAUSED" => Ok(TaskState::Paused),
             "RESTARTING" => Ok(TaskState::Restarting),
             "LOST" => Ok(TaskState::Lost),
             "CREATED" => Ok(TaskState::Created),
             "DEAD" => Ok(TaskState::Dead),
+            "STOPPED" => Ok(TaskState::Stopped),
             _ => Err(anyhow!("unimplemneted state")),
         }
     }
 }
 
+impl From<String> for TaskState {
+    fn from(state: String) -> Self {
+        TaskState::from_str(&state).unwrap_or(TaskState::Unknown(state))
+    }
+}
+
+impl From<TaskState> for String {
+    fn from(state: TaskState) -> Self {
+        state.to_string()
+    }
+}
+
 impl Display for TaskState {
     fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
         match self {
@@ -141,6 +168,8 @@ impl Display for TaskState {
             Self::Restarting => write!(f, "RESTARTING"),
             Self::Created => write!(f, "CREATED"),
             Self::Dead => write!(f, "DEAD"),
+            Self::Stopped => write!(f, "STOPPED"),
+            Self::Unknown(state) => write!(f, "{state}"),
         }
     }
 }
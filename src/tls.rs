@@ -0,0 +1,183 @@
+use std::fs::File;
+use std::io::BufReader;
+use std::path::Path;
+use std::sync::Arc;
+use std::time::SystemTime;
+
+use anyhow::{anyhow, ensure, Context, Result};
+use rustls::client::{ServerCertVerified, ServerCertVerifier};
+use rustls::{Certificate, ClientConfig, OwnedTrustAnchor, PrivateKey, RootCertStore, ServerName};
+
+use crate::config::ClusterContext;
+
+/// Builds the TLS config of a cluster, `None` when the cluster uses the defaults
+pub fn client_config(cluster: &ClusterContext) -> Result<Option<Arc<ClientConfig>>> {
+    if cluster.ca_file.is_none()
+        && cluster.client_cert.is_none()
+        && cluster.client_key.is_none()
+        && !cluster.insecure_skip_verify
+    {
+        return Ok(None);
+    }
+
+    let mut roots = RootCertStore::empty();
+    match &cluster.ca_file {
+        Some(ca_file) => {
+            for cert in read_certs(ca_file)? {
+                roots
+                    .add(&cert)
+                    .with_context(|| format!("invalid CA certificate in \"{}\"", ca_file))?;
+            }
+        }
+        None => roots.add_trust_anchors(webpki_roots::TLS_SERVER_ROOTS.iter().map(|ta| {
+            OwnedTrustAnchor::from_subject_spki_name_constraints(
+                ta.subject,
+                ta.spki,
+                ta.name_constraints,
+            )
+        })),
+    }
+
+    let builder = ClientConfig::builder()
+        .with_safe_defaults()
+        .with_root_certificates(roots);
+    let mut config = match (&cluster.client_cert, &cluster.client_key) {
+        (Some(cert), Some(key)) => builder
+            .with_client_auth_cert(read_certs(cert)?, read_key(key)?)
+            .context("invalid client certificate or key")?,
+        (None, None) => builder.with_no_client_auth(),
+        _ => {
+            return Err(anyhow!(
+                "cluster: \"{}\" must set both client-cert and client-key",
+                cluster.name
+            ))
+        }
+    };
+    if cluster.insecure_skip_verify {
+        config
+            .dangerous()
+            .set_certificate_verifier(Arc::new(SkipVerification));
+    }
+    Ok(Some(Arc::new(config)))
+}
+
+/// Whether an error of a request was caused by a failed TLS handshake
+pub fn is_handshake_error(error: &(dyn std::error::Error + 'static)) -> bool {
+    let mut source = Some(error);
+    while let Some(error) = source {
+        if error.is::<rustls::Error>() {
+            return true;
+        }
+        // io errors wrapping a rustls error do not expose it as their source
+        if let Some(io) = error.downcast_ref::<std::io::Error>() {
+            if io
+                .get_ref()
+                .is_some_and(|inner| inner.is::<rustls::Error>())
+            {
+                return true;
+            }
+        }
+        source = error.source();
+    }
+    false
+}
+
+fn read_certs(path: &str) -> Result<Vec<Certificate>> {
+    let certs = rustls_pemfile::certs(&mut open(path)?)
+        .with_context(|| format!("invalid PEM certificates in \"{}\"", path))?;
+    ensure!(!certs.is_empty(), "no certificates found in \"{}\"", path);
+    Ok(certs.into_iter().map(Certificate).collect())
+}
+
+fn read_key(path: &str) -> Result<PrivateKey> {
+    for item in rustls_pemfile::read_all(&mut open(path)?)
+        .with_context(|| format!("invalid PEM private key in \"{}\"", path))?
+    {
+        if let rustls_pemfile::Item::RSAKey(key)
+        | rustls_pemfile::Item::PKCS8Key(key)
+        | rustls_pemfile::Item::ECKey(key) = item
+        {
+            return Ok(PrivateKey(key));
+        }
+    }
+    Err(anyhow!("no private key found in \"{}\"", path))
+}
+
+fn open(path: &str) -> Result<BufReader<File>> {
+    let expanded = shellexpand::tilde(path);
+    let file = File::open(Path::new(expanded.as_ref()))
+        .with_context(|| format!("could not open \"{}\"", path))?;
+    Ok(BufReader::new(file))
+}
+
+/// Accepts any server certificate, used by clusters with `insecure-skip-verify`
+struct SkipVerification;
+
+impl ServerCertVerifier for SkipVerification {
+    fn verify_server_cert(
+        &self,
+        _end_entity: &Certificate,
+        _intermediates: &[Certificate],
+        _server_name: &ServerName,
+        _scts: &mut dyn Iterator<Item = &[u8]>,
+        _ocsp_response: &[u8],
+        _now: SystemTime,
+    ) -> Result<ServerCertVerified, rustls::Error> {
+        Ok(ServerCertVerified::assertion())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::error::KofrError;
+
+    fn cluster() -> ClusterContext {
+        ClusterContext {
+            name: "dev".to_string(),
+            hosts: vec!["https://localhost:8083".to_string()],
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn test_default_clusters_keep_the_default_config() {
+        assert!(client_config(&cluster()).unwrap().is_none());
+    }
+
+    #[test]
+    fn test_client_cert_requires_a_key() {
+        let cluster = ClusterContext {
+            client_cert: Some("client.pem".to_string()),
+            ..cluster()
+        };
+
+        let err = client_config(&cluster).unwrap_err();
+        assert!(err
+            .to_string()
+            .contains("must set both client-cert and client-key"));
+    }
+
+    #[test]
+    fn test_unreadable_ca_file_is_a_config_error() {
+        let dir = tempfile::tempdir().unwrap();
+        let cluster = ClusterContext {
+            ca_file: Some(dir.path().join("ca.pem").to_string_lossy().into_owned()),
+            ..cluster()
+        };
+
+        let err = crate::connect::cluster_agent(&cluster).unwrap_err();
+        assert!(matches!(err, KofrError::Config(_)));
+        assert_eq!(err.exit_code(), exitcode::CONFIG);
+    }
+
+    #[test]
+    fn test_insecure_skip_verify_builds_a_config() {
+        let cluster = ClusterContext {
+            insecure_skip_verify: true,
+            ..cluster()
+        };
+
+        assert!(client_config(&cluster).unwrap().is_some());
+    }
+}
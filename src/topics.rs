@@ -2,33 +2,55 @@ use std::collections::HashMap;
 
 use serde::{Deserialize, Serialize};
 
-use anyhow::{anyhow, Context, Result};
+use crate::connect::HTTPClient;
+use crate::error::{KofrError, Result};
 
-pub fn list_topics(host: &str, connector_name: &str) -> Result<Topic> {
-    let endpoint = crate::tasks::valid_uri(host);
-    let endpoint = format!("{}/{}/topics", &endpoint, connector_name);
-    match ureq::get(&endpoint)
-        .set("Accept", "application/json")
-        .call()
-    {
-        Ok(response) => response
-            .into_json::<Topic>()
-            .context("invalid json returned from api"),
-        Err(ureq::Error::Status(_, response)) => Err(anyhow!("{}", response.into_string()?)),
-        Err(err) => Err(anyhow!("{}", err)),
+impl HTTPClient {
+    /// topics used by a connector since it was created or its topics were reset, wrapping GET
+    /// request to /connectors/<name>/topics
+    pub fn list_topics(&self, connector_name: &str) -> Result<Topic> {
+        match self.send(
+            |uri| {
+                self.config
+                    .http_agent
+                    .get(&format!(
+                        "{}/{}/topics",
+                        self.valid_uri(uri),
+                        connector_name
+                    ))
+                    .set("Accept", "application/json")
+            },
+            None,
+        ) {
+            Ok(response) => Ok(response.into_json::<Topic>()?),
+            Err(ureq::Error::Status(404, _)) => {
+                Err(KofrError::not_found("connector", connector_name))
+            }
+            Err(err) => Err(err.into()),
+        }
     }
-}
 
-pub fn reset(host: &str, connector_name: &str) -> Result<()> {
-    let endpoint = crate::tasks::valid_uri(host);
-    let endpoint = format!("{}/{}/topics/reset", &endpoint, connector_name);
-    match ureq::put(&endpoint)
-        .set("Accept", "application/json")
-        .call()
-    {
-        Ok(_) => Ok(()),
-        Err(ureq::Error::Status(_, response)) => Err(anyhow!("{}", response.into_string()?)),
-        Err(err) => Err(anyhow!("{}", err)),
+    /// wraps PUT request to /connectors/<name>/topics/reset
+    pub fn reset_topics(&self, connector_name: &str) -> Result<()> {
+        match self.send(
+            |uri| {
+                self.config
+                    .http_agent
+                    .put(&format!(
+                        "{}/{}/topics/reset",
+                        self.valid_uri(uri),
+                        connector_name
+                    ))
+                    .set("Accept", "application/json")
+            },
+            None,
+        ) {
+            Ok(_) => Ok(()),
+            Err(ureq::Error::Status(404, _)) => {
+                Err(KofrError::not_found("connector", connector_name))
+            }
+            Err(err) => Err(err.into()),
+        }
     }
 }
 
@@ -0,0 +1,183 @@
+use std::collections::HashMap;
+use std::io::{IsTerminal, Write};
+use std::time::Duration;
+
+use anyhow::Result;
+use kofr::connect::{ConnectorStatus, VerboseConnector};
+use kofr::tasks::TaskStatus;
+use tabled::settings::{locator::ByColumnName, Disable};
+
+use crate::output::Printable;
+
+const CLEAR_SCREEN: &str = "\x1b[2J\x1b[H";
+const BOLD_YELLOW: &str = "\x1b[1;33m";
+const RESET: &str = "\x1b[0m";
+
+/// A table that can be redrawn by `--watch`
+pub trait Watchable {
+    /// table with exactly one line per row, below a single header line
+    fn watch_table(&self, wide: bool) -> String;
+    /// key and state of every row, in the order of the table rows
+    fn row_states(&self) -> Vec<(String, String)>;
+}
+
+/// Polls and redraws a table every `interval` until interrupted. Rows whose state changed
+/// since the previous poll are marked with a `*` and highlighted on terminals. Failed polls
+/// are shown in place of the table so that watching survives workers restarting.
+pub fn watch<T, F>(title: &str, interval: Duration, wide: bool, mut poll: F) -> Result<()>
+where
+    T: Watchable,
+    F: FnMut() -> Result<T>,
+{
+    let terminal = std::io::stdout().is_terminal();
+    let color = crate::diff::use_color();
+    let mut previous: Option<HashMap<String, String>> = None;
+    loop {
+        let polled = poll();
+        if terminal {
+            print!("{CLEAR_SCREEN}");
+        }
+        println!(
+            "Every {}: {}\n",
+            humantime::format_duration(interval),
+            title
+        );
+        match polled {
+            Ok(table) => {
+                let states = table.row_states();
+                let rendered = table.watch_table(wide);
+                print!(
+                    "{}",
+                    highlight(&rendered, &states, previous.as_ref(), color)
+                );
+                previous = Some(states.into_iter().collect());
+            }
+            Err(e) => println!("error: {e:#}"),
+        }
+        if !terminal {
+            println!();
+        }
+        std::io::stdout().flush()?;
+        std::thread::sleep(interval);
+    }
+}
+
+/// Marks table rows whose state differs from the previous poll, rows that were not present
+/// in the previous poll are marked as well. Nothing is marked on the first poll.
+fn highlight(
+    table: &str,
+    states: &[(String, String)],
+    previous: Option<&HashMap<String, String>>,
+    color: bool,
+) -> String {
+    let mut out = String::new();
+    for (i, line) in table.lines().enumerate() {
+        let changed = match (i.checked_sub(1).and_then(|row| states.get(row)), previous) {
+            (Some((key, state)), Some(previous)) => previous.get(key) != Some(state),
+            _ => false,
+        };
+        if !changed {
+            out.push_str(line);
+        } else {
+            let marked = format!("*{}", line.get(1..).unwrap_or_default());
+            if color {
+                out.push_str(&format!("{BOLD_YELLOW}{marked}{RESET}"));
+            } else {
+                out.push_str(&marked);
+            }
+        }
+        out.push('\n');
+    }
+    out
+}
+
+impl Watchable for Vec<VerboseConnector> {
+    fn watch_table(&self, wide: bool) -> String {
+        self.table(wide)
+    }
+
+    fn row_states(&self) -> Vec<(String, String)> {
+        self.iter()
+            .map(|c| (c.name.to_string(), c.state.to_string()))
+            .collect()
+    }
+}
+
+impl Watchable for ConnectorStatus {
+    fn watch_table(&self, _wide: bool) -> String {
+        let connector = StatusRow {
+            component: "connector".to_string(),
+            state: self.connector_state.state.to_string(),
+            worker_id: self.connector_state.worker_id.clone(),
+        };
+        let tasks = self.tasks.iter().map(|t| StatusRow {
+            component: format!("task-{}", t.id),
+            state: t.state.to_string(),
+            worker_id: t.worker_id.clone(),
+        });
+        crate::output::table(std::iter::once(connector).chain(tasks)).to_string()
+    }
+
+    fn row_states(&self) -> Vec<(String, String)> {
+        let connector = (
+            "connector".to_string(),
+            self.connector_state.state.to_string(),
+        );
+        let tasks = self
+            .tasks
+            .iter()
+            .map(|t| (format!("task-{}", t.id), t.state.to_string()));
+        std::iter::once(connector).chain(tasks).collect()
+    }
+}
+
+#[derive(tabled::Tabled)]
+struct StatusRow {
+    #[tabled(rename = "COMPONENT")]
+    component: String,
+    #[tabled(rename = "STATE")]
+    state: String,
+    #[tabled(rename = "WORKER_ID")]
+    worker_id: String,
+}
+
+impl Watchable for Vec<TaskStatus> {
+    /// traces span many lines, watch `task status` of a single task to see them
+    fn watch_table(&self, _wide: bool) -> String {
+        crate::output::table(self)
+            .with(Disable::column(ByColumnName::new("TRACE")))
+            .to_string()
+    }
+
+    fn row_states(&self) -> Vec<(String, String)> {
+        self.iter()
+            .map(|t| (t.id.to_string(), t.state.to_string()))
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_highlighting_changed_rows() {
+        let table = " NAME   STATE  \n a      RUNNING\n b      FAILED \n c      RUNNING\n";
+        let states = vec![
+            ("a".to_string(), "RUNNING".to_string()),
+            ("b".to_string(), "FAILED".to_string()),
+            ("c".to_string(), "RUNNING".to_string()),
+        ];
+
+        assert_eq!(highlight(table, &states, None, false), table);
+
+        let previous = HashMap::from([
+            ("a".to_string(), "RUNNING".to_string()),
+            ("b".to_string(), "RUNNING".to_string()),
+        ]);
+        assert_eq!(
+            highlight(table, &states, Some(&previous), false),
+            " NAME   STATE  \n a      RUNNING\n*b      FAILED \n*c      RUNNING\n"
+        );
+    }
+}
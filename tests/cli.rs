@@ -22,7 +22,7 @@ fn test_invalid_config_file_format() {
 #[test]
 fn test_config_file_not_found() {
     let mut cmd = Command::cargo_bin("kofr").unwrap();
-    cmd.arg(format!("--config-file=does-not-exist"))
+    cmd.arg("--config-file=does-not-exist")
         .arg("ls")
         .assert()
         .failure()
@@ -2,13 +2,11 @@ use tempfile::NamedTempFile;
 
 pub fn config_invalid_format() -> NamedTempFile {
     let config_file = tempfile::Builder::new().tempfile().unwrap();
-    let config = format!(
-        r#"
+    let config = r#"
 current-cluster: test
 #clusters:
 - name: test
-"#,
-    );
+"#;
 
     std::fs::write(config_file.path(), config).unwrap();
     config_file